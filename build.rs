@@ -1,9 +1,59 @@
-#[cfg(target_os = "windows")]
+use std::process::Command;
+
 fn main() {
-    let mut res = winres::WindowsResource::new();
-    res.set_icon("icon.ico");
-    res.compile().expect("Failed to compile resources");
+    // About ダイアログに表示するビルド情報
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=GIT_COMMIT={}", commit);
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    // ブランチ上でのコミットは HEAD ではなく参照先のファイル (または packed-refs) が変わる
+    if let Some(head_ref) = std::fs::read_to_string(".git/HEAD")
+        .ok()
+        .and_then(|head| head.trim().strip_prefix("ref: ").map(|r| r.to_string()))
+    {
+        println!("cargo:rerun-if-changed=.git/{}", head_ref);
+    }
+    if std::path::Path::new(".git/packed-refs").exists() {
+        println!("cargo:rerun-if-changed=.git/packed-refs");
+    }
+
+    // 実際に解決された egui / eframe のバージョンは Cargo.lock から読む
+    let lock = std::fs::read_to_string("Cargo.lock").unwrap_or_default();
+    for name in ["egui", "eframe"] {
+        let version = locked_version(&lock, name).unwrap_or_else(|| "unknown".to_string());
+        println!(
+            "cargo:rustc-env={}_VERSION={}",
+            name.to_uppercase(),
+            version
+        );
+    }
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    #[cfg(target_os = "windows")]
+    {
+        let mut res = winres::WindowsResource::new();
+        res.set_icon("icon.ico");
+        res.compile().expect("Failed to compile resources");
+    }
 }
 
-#[cfg(not(target_os = "windows"))]
-fn main() {}
+/// Cargo.lock の [[package]] から指定した名前のパッケージのバージョンを探す
+fn locked_version(lock: &str, name: &str) -> Option<String> {
+    let name_line = format!("name = \"{}\"", name);
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == name_line {
+            let version = lines.next()?.trim();
+            return version
+                .strip_prefix("version = \"")
+                .and_then(|v| v.strip_suffix('"'))
+                .map(|v| v.to_string());
+        }
+    }
+    None
+}
@@ -1,17 +1,51 @@
 #![cfg_attr(all(windows, not(debug_assertions)), windows_subsystem = "windows")]
 
-use chrono::{Duration, TimeZone, Utc};
+use chrono::{TimeZone, Utc};
 use eframe;
 use egui;
 use egui::Color32;
-use egui_plot::{Legend, Line, PlotPoints, PlotUi};
+use egui_plot::{
+    Legend, Line, MarkerShape, PlotPoint, PlotPoints, PlotUi, Points, Polygon, Text, VLine,
+};
+use regex::Regex;
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
 use serde_json;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs;
 use std::ops::RangeInclusive;
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+
+// ウィンドウタイトル (ファイルを開いている間はファイル名を後ろに付ける)
+const APP_TITLE: &str = "Log Analyzer";
+
+// ユーザー設定ファイル (カレントディレクトリ基準)
+const SETTINGS_FILE: &str = "user_settings.json";
+
+// 設定ウィンドウを開閉するショートカット (Ctrl+, / macOS は Cmd+,)
+const SETTINGS_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Comma);
+
+// プロットの画像をクリップボードにコピーするショートカット (Ctrl+Shift+C / macOS は Cmd+Shift+C)
+const COPY_PLOT_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::C,
+);
+
+// 自動保存で、最後に設定を変えてから書き込むまでの待ち時間 [秒]
+const SETTINGS_AUTO_SAVE_DELAY: f64 = 1.0;
+
+// シグナル名を入力して表示を切り替えるパレットのショートカット (Ctrl+P / macOS は Cmd+P)
+const PALETTE_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::P);
+
+// パレットに並べる候補の最大数
+const PALETTE_MAX_MATCHES: usize = 20;
+
+// グループ見出しのツールチップに並べるシグナル名の最大数
+const GROUP_TOOLTIP_MAX_SIGNALS: usize = 30;
 
 // ユーザー設定
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -20,12 +54,183 @@ struct ConversionScriptSetting {
     script_path: String,
     // 例: [".log", ".txt"]
     extensions: Vec<String>,
+    // このスクリプトだけに設定する環境変数 (全体設定より優先)
+    #[serde(default)]
+    env: Vec<(String, String)>,
+    // スクリプトに渡す引数のテンプレート (空なら "{input}")
+    // {output} を含めると一時ファイルに出力させ、入力ファイルの隣に JSON を作らない
+    #[serde(default)]
+    arguments: String,
+}
+
+impl ConversionScriptSetting {
+    /// 引数テンプレートを展開する (空白区切り)
+    /// {input}: 変換元ファイル, {stem}: その拡張子を除いた名前, {dir}: そのディレクトリ,
+    /// {output}: 一時出力ファイル
+    fn expand_arguments(&self, input: &str, output: &str) -> Vec<String> {
+        let template = if self.arguments.trim().is_empty() {
+            "{input}"
+        } else {
+            &self.arguments
+        };
+        let input_path = std::path::Path::new(input);
+        let stem = input_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let dir = input_path
+            .parent()
+            .map(|d| d.to_string_lossy().to_string())
+            .unwrap_or_default();
+        template
+            .split_whitespace()
+            .map(|arg| {
+                arg.replace("{input}", input)
+                    .replace("{stem}", &stem)
+                    .replace("{dir}", &dir)
+                    .replace("{output}", output)
+            })
+            .collect()
+    }
+
+    /// 変換結果を一時ファイルに出力させるか
+    fn uses_output_file(&self) -> bool {
+        self.arguments.contains("{output}")
+    }
+}
+
+// シグナル名の正規表現 → グループ名 のグルーピングルール
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct GroupingRule {
+    pattern: String,
+    group: String,
+}
+
+// アナログシグナルのしきい値 (超えた部分を警告色で描く)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct AnalogThreshold {
+    signal: String,
+    value: f64,
+}
+
+// シグナル名ごとの物理単位への換算 (表示値 = 生の値 * scale + offset)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SignalUnit {
+    signal: String,
+    unit: String,
+    #[serde(default = "default_unit_scale")]
+    scale: f64,
+    #[serde(default)]
+    offset: f64,
+}
+
+fn default_unit_scale() -> f64 {
+    1.0
+}
+
+impl SignalUnit {
+    /// 生の値を換算し、単位を付けた文字列にする
+    fn format(&self, raw: f64) -> String {
+        let value = raw * self.scale + self.offset;
+        if self.unit.is_empty() {
+            format!("{:.3}", value)
+        } else {
+            format!("{:.3} {}", value, self.unit)
+        }
+    }
+}
+
+/// シグナル名に対応する単位設定を探す
+fn find_signal_unit<'a>(units: &'a [SignalUnit], signal: &str) -> Option<&'a SignalUnit> {
+    units.iter().find(|u| u.signal == signal)
+}
+
+/// 有効なグルーピングルールをコンパイルする (不正なパターンは無視)
+fn compile_grouping_rules(rules: &[GroupingRule]) -> Vec<(Regex, String)> {
+    rules
+        .iter()
+        .filter(|r| !r.pattern.is_empty() && !r.group.is_empty())
+        .filter_map(|r| Regex::new(&r.pattern).ok().map(|re| (re, r.group.clone())))
+        .collect()
+}
+
+// 時間軸の表示タイムゾーン
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+enum DisplayTimezone {
+    #[serde(rename = "UTC")]
+    Utc,
+    Local,
+}
+
+// ファイルを読み込んだときのプロットの表示範囲の扱い
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+enum ViewOnLoad {
+    // 今のズームのまま
+    Keep,
+    // 新しいファイルで全体の時刻範囲が広がったときだけデータ全体に合わせる
+    FitIfExtended,
+    // 常にデータ全体に合わせる
+    Fit,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 struct UserSettings {
     python_path: String,
     conversion_scripts: Vec<ConversionScriptSetting>,
+    // 全スクリプト共通で設定する環境変数 (例: PYTHONPATH)
+    script_env: Vec<(String, String)>,
+    // 時間軸に表示する秒の小数桁数 (0〜6)
+    time_axis_decimals: usize,
+    // true: 可視シグナルの並び順でパレットを割り当てる (従来の動作)
+    // false: ファイル名+シグナル名のハッシュで色を固定する
+    color_by_position: bool,
+    display_timezone: DisplayTimezone,
+    view_on_load: ViewOnLoad,
+    // 読み込み時に default_visibility で指定のないシグナルを表示する
+    default_show_all: bool,
+    // 読み込み時にエッジのないシグナルを非表示にする (default_visibility の指定が優先)
+    hide_idle_on_load: bool,
+    // true: 先頭のシグナルを最下段に置く (オシロスコープ風)
+    stack_bottom_up: bool,
+    // レーンの間に区切り線を描く
+    lane_separators: bool,
+    // マウス位置に縦線と時刻を表示する
+    hover_crosshair: bool,
+    // 監視モードでファイルの更新を確認する間隔 [ms]
+    watch_interval_ms: u64,
+    // true: 読み込む JSON の // と /* */ コメントを取り除いてから解釈する
+    tolerant_json: bool,
+    // この長さ [ms] 未満の High パルスをチャタリングとして捨てる (0 なら無効)
+    debounce_ms: f64,
+    // true: OFF が来ないまま終わった ON を、ファイルの末尾まで High とみなす
+    extend_unclosed_on: bool,
+    // true: 未知の type のログを 0.2 秒の区間として描かずに警告する
+    strict_kinds: bool,
+    // true: 設定を変えるたびに (少し待ってから) 設定ファイルへ書き込む
+    auto_save: bool,
+    // 同時に表示できるシグナル数の上限 (0 なら無制限)
+    max_visible_signals: usize,
+    // true: 上限を超えたら最初に表示したシグナルから非表示にする (false なら警告のみ)
+    evict_oldest_visible: bool,
+    // 中央ペインのプロットの背景色とグリッド線の色 (軸の目盛りと凡例の文字もこの色になる)
+    plot_background_color: [u8; 3],
+    plot_grid_color: [u8; 3],
+    // 相対パスのスクリプトを解決する基準ディレクトリ (空なら設定ファイルの場所)
+    script_base_dir: String,
+    // シグナルの描画色パレット (空ならデフォルト)
+    color_palette: Vec<[u8; 3]>,
+    // グループ分けに使うログのフィールド名 (空なら group フィールド)
+    group_by_field: String,
+    // group が無いログに適用するグルーピングルール (上から順に評価)
+    grouping_rules: Vec<GroupingRule>,
+    // シグナル名ごとのアナログ値のしきい値
+    analog_thresholds: Vec<AnalogThreshold>,
+    // シグナル名ごとの単位と換算
+    signal_units: Vec<SignalUnit>,
+    // 区間数がしきい値を超えるシグナルを表示解像度に合わせて間引く
+    lod_enabled: bool,
+    lod_max_intervals: usize,
 }
 
 impl Default for UserSettings {
@@ -36,9 +241,122 @@ impl Default for UserSettings {
                 name: "Default Conversion".to_string(),
                 script_path: "scripts/convert.py".to_string(),
                 extensions: vec![".log".to_string(), ".txt".to_string()],
+                env: Vec::new(),
+                arguments: String::new(),
             }],
+            script_env: Vec::new(),
+            time_axis_decimals: 3,
+            color_by_position: false,
+            display_timezone: DisplayTimezone::Utc,
+            view_on_load: ViewOnLoad::FitIfExtended,
+            default_show_all: false,
+            hide_idle_on_load: false,
+            stack_bottom_up: false,
+            lane_separators: false,
+            hover_crosshair: false,
+            watch_interval_ms: 1000,
+            tolerant_json: false,
+            debounce_ms: 0.0,
+            extend_unclosed_on: false,
+            strict_kinds: false,
+            auto_save: false,
+            max_visible_signals: 0,
+            evict_oldest_visible: false,
+            plot_background_color: [10, 10, 10],
+            plot_grid_color: [140, 140, 140],
+            script_base_dir: String::new(),
+            color_palette: default_color_palette(),
+            group_by_field: String::new(),
+            grouping_rules: Vec::new(),
+            analog_thresholds: Vec::new(),
+            signal_units: Vec::new(),
+            lod_enabled: true,
+            lod_max_intervals: 2000,
+        }
+    }
+}
+
+impl UserSettings {
+    /// 共有された設定の変換関連の項目だけを取り込む (表示設定は今のものを残す)
+    /// 同名のスクリプト・同名の環境変数は取り込む側で上書きする
+    fn merge_conversion_settings(&mut self, other: UserSettings) {
+        if !other.python_path.is_empty() {
+            self.python_path = other.python_path;
         }
+        if !other.script_base_dir.is_empty() {
+            self.script_base_dir = other.script_base_dir;
+        }
+        for script in other.conversion_scripts {
+            match self
+                .conversion_scripts
+                .iter_mut()
+                .find(|s| s.name == script.name)
+            {
+                Some(existing) => *existing = script,
+                None => self.conversion_scripts.push(script),
+            }
+        }
+        for (key, value) in other.script_env {
+            match self.script_env.iter_mut().find(|(k, _)| *k == key) {
+                Some(existing) => existing.1 = value,
+                None => self.script_env.push((key, value)),
+            }
+        }
+        if !other.group_by_field.is_empty() {
+            self.group_by_field = other.group_by_field;
+        }
+        for rule in other.grouping_rules {
+            if !self
+                .grouping_rules
+                .iter()
+                .any(|r| r.pattern == rule.pattern && r.group == rule.group)
+            {
+                self.grouping_rules.push(rule);
+            }
+        }
+    }
+}
+
+/// 設定ファイルの内容を検証して読み込む (JSON オブジェクトでなければエラー)
+fn read_settings_file(path: &std::path::Path) -> Result<UserSettings, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("File read error: {}", e))?;
+    let value: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    if !value.is_object() {
+        return Err("The settings file must contain a JSON object.".to_string());
     }
+    serde_json::from_value(value).map_err(|e| format!("Invalid settings: {}", e))
+}
+
+/// デフォルトの描画色パレット
+fn default_color_palette() -> Vec<[u8; 3]> {
+    [
+        Color32::RED,
+        Color32::GREEN,
+        Color32::BLUE,
+        Color32::YELLOW,
+        Color32::LIGHT_BLUE,
+        Color32::LIGHT_GREEN,
+        Color32::WHITE,
+        Color32::GOLD,
+    ]
+    .iter()
+    .map(|c| [c.r(), c.g(), c.b()])
+    .collect()
+}
+
+/// 色覚多様性に配慮したパレット (Okabe-Ito、黒は暗い背景用に白へ置き換え)
+fn colorblind_safe_palette() -> Vec<[u8; 3]> {
+    vec![
+        [230, 159, 0],
+        [86, 180, 233],
+        [0, 158, 115],
+        [240, 228, 66],
+        [0, 114, 178],
+        [213, 94, 0],
+        [204, 121, 167],
+        [255, 255, 255],
+    ]
 }
 
 // ログのエントリとデータファイルの構造体
@@ -52,6 +370,9 @@ struct LogEntry {
     group: Option<String>,
     value: serde_json::Value,
     comment: Option<String>,
+    // 上記以外のフィールド (module や thread など。設定でグループ分けに使える)
+    #[serde(flatten)]
+    extra: HashMap<String, serde_json::Value>,
 
     // 内部処理用
     #[serde(skip_serializing, skip_deserializing)]
@@ -64,6 +385,34 @@ struct DataFile {
     default_visibility: Option<Vec<VisibilityEntry>>,
 }
 
+// 区間エクスポート (マージ後の on_intervals) の1シグナル分
+#[derive(Debug, Serialize)]
+struct IntervalExport {
+    file: String,
+    signal: String,
+    // [開始, 終了] (ISO 8601)
+    intervals: Vec<[String; 2]>,
+}
+
+// 複数のファイルと表示設定をまとめて開くためのマニフェスト
+// { "files": [ { "path": "...", "offset": 0.5, "visible": ["SIG_A", ...] } ] }
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestFile {
+    // マニフェストのあるディレクトリからの相対パスも可
+    path: String,
+    // 時刻をずらす量 [秒]
+    #[serde(default)]
+    offset: f64,
+    // 表示するシグナル名 (指定すると、それ以外は非表示にする)
+    #[serde(default)]
+    visible: Option<Vec<String>>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct VisibilityEntry {
     group: String,
@@ -72,22 +421,741 @@ struct VisibilityEntry {
 }
 
 // タイムチャートの描画用データ
+#[derive(Clone)]
 struct Interval {
     start: f64,
     end: f64,
 }
 
+// 「Compress Idle」表示で、長い無通信区間を一定幅に縮める時間軸の写像
+#[derive(Default)]
+struct TimeWarp {
+    // 縮める区間 (実時刻の開始, 実時刻の終了, 表示上の開始位置)
+    gaps: Vec<(f64, f64, f64)>,
+    // 縮めた区間の表示上の幅 [s]
+    width: f64,
+}
+
+impl TimeWarp {
+    /// 昇順の時刻列から、threshold より長い隙間を width に縮める写像を作る
+    fn from_times(times: &[f64], threshold: f64, width: f64) -> Self {
+        let mut gaps = Vec::new();
+        let mut removed = 0.0;
+        for w in times.windows(2) {
+            let (start, end) = (w[0], w[1]);
+            if end - start > threshold {
+                gaps.push((start, end, start - removed));
+                removed += end - start - width;
+            }
+        }
+        Self { gaps, width }
+    }
+
+    /// 実時刻 → 表示座標
+    fn to_display(&self, t: f64) -> f64 {
+        let i = self.gaps.partition_point(|g| g.1 <= t);
+        match self.gaps.get(i) {
+            Some(&(start, end, disp)) if t > start => {
+                disp + (t - start) / (end - start) * self.width
+            }
+            _ => t - self.removed_before(i),
+        }
+    }
+
+    /// 表示座標 → 実時刻 (軸ラベル用)
+    fn to_real(&self, x: f64) -> f64 {
+        let i = self.gaps.partition_point(|g| g.2 + self.width <= x);
+        match self.gaps.get(i) {
+            Some(&(start, end, disp)) if x > disp => {
+                start + (x - disp) / self.width * (end - start)
+            }
+            _ => x + self.removed_before(i),
+        }
+    }
+
+    /// i 番目より前の区間を縮めたことで詰めた時間
+    fn removed_before(&self, i: usize) -> f64 {
+        i.checked_sub(1)
+            .map(|k| {
+                let (_, end, disp) = self.gaps[k];
+                end - (disp + self.width)
+            })
+            .unwrap_or(0.0)
+    }
+
+    fn warp_intervals(&self, intervals: &[Interval]) -> Vec<Interval> {
+        intervals
+            .iter()
+            .map(|iv| Interval {
+                start: self.to_display(iv.start),
+                end: self.to_display(iv.end),
+            })
+            .collect()
+    }
+}
+
 struct SignalData {
     name: String,
     on_intervals: Vec<Interval>,
+    // STATE 種別の (時刻, 値) サンプル
+    state_samples: Vec<(f64, String)>,
+    // ANALOG 種別の (時刻, 数値) サンプルと値の範囲
+    analog_samples: Vec<(f64, f64)>,
+    value_min: f64,
+    value_max: f64,
+    // アナログ表示の縦方向の倍率と位置 (正規化した 0〜1 の値に対して適用する)
+    analog_gain: f64,
+    analog_offset: f64,
+    // 中央ペインでの線の太さ
+    line_width: f32,
     is_on: Option<f64>,
     visible: bool,
     color: Color32,
+
+    // 統計情報 (recalc で計算)
+    // エッジが一度もない (ずっと Low のまま) シグナル
+    idle: bool,
+    total_high: f64,
+    // キャプチャ全体に対する High 時間の割合 [%] (時間幅が 0 のファイルでは None)
+    high_percent: Option<f64>,
+    // 連続する区間の開始時刻の間隔の (平均, 標準偏差) [秒] (区間が2つ未満なら None)
+    period: Option<(f64, f64)>,
+    first_edge: Option<f64>,
+    last_edge: Option<f64>,
+}
+
+// 中央ペインで描画する可視シグナル1本分
+struct PlotSignal<'a> {
+    label: String,
+    color: Color32,
+    sig: &'a SignalData,
+    file_index: usize,
+    // 上から何番目のレーンに描くか
+    lane: usize,
+    // 派生シグナルの場合は変換後の区間 (sig は元シグナル)
+    derived_intervals: Option<Vec<Interval>>,
+    // 値検索に一致したログの時刻
+    search_hits: Vec<f64>,
+}
+
+// 「Export SVG」で書き出す図の内容 (座標はプロットの表示座標)
+#[derive(Default)]
+struct SvgFigure {
+    // (色, 線幅, 折れ線の点)
+    lines: Vec<(Color32, f32, Vec<[f64; 2]>)>,
+    // 状態値のボックス (色, 開始, 終了, レーンの y_offset, 値)
+    boxes: Vec<(Color32, f64, f64, f64, String)>,
+    // レーンのラベル (y_offset, ラベル)
+    labels: Vec<(f64, String)>,
+}
+
+impl SvgFigure {
+    const WIDTH: f64 = 1200.0;
+    const LABEL_WIDTH: f64 = 260.0;
+    const LANE_HEIGHT: f64 = 40.0;
+    const MARGIN: f64 = 10.0;
+    const AXIS_HEIGHT: f64 = 30.0;
+    const TICKS: usize = 8;
+
+    /// x_range の範囲を、y = 0 〜 y_max のレーン領域として SVG 文字列にする
+    fn render(
+        &self,
+        x_range: (f64, f64),
+        y_max: f64,
+        background: Color32,
+        grid: Color32,
+        tick_label: impl Fn(f64) -> String,
+    ) -> String {
+        let (x0, x1) = x_range;
+        let plot_left = Self::LABEL_WIDTH;
+        let plot_width = Self::WIDTH - Self::LABEL_WIDTH - Self::MARGIN;
+        let plot_height = (y_max / 2.0).max(1.0) * Self::LANE_HEIGHT;
+        let height = Self::MARGIN + plot_height + Self::AXIS_HEIGHT;
+        let span = if x1 > x0 { x1 - x0 } else { 1.0 };
+        let sx = |x: f64| plot_left + (x - x0) / span * plot_width;
+        let sy = |y: f64| Self::MARGIN + (y_max - y) / y_max.max(1.0) * plot_height;
+
+        let mut out = String::new();
+        out.push_str(&format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"monospace\" font-size=\"12\">\n",
+            w = Self::WIDTH,
+            h = height
+        ));
+        out.push_str(&format!(
+            "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+            svg_color(background)
+        ));
+        out.push_str(&format!(
+            "<clipPath id=\"plot\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>\n",
+            plot_left,
+            Self::MARGIN,
+            plot_width,
+            plot_height
+        ));
+
+        // 時間軸の目盛り (縦のグリッド線と時刻ラベル)
+        let axis_y = Self::MARGIN + plot_height;
+        for i in 0..=Self::TICKS {
+            let x = x0 + span * i as f64 / Self::TICKS as f64;
+            let px = sx(x);
+            out.push_str(&format!(
+                "<line x1=\"{px:.2}\" y1=\"{}\" x2=\"{px:.2}\" y2=\"{axis_y:.2}\" stroke=\"{}\" stroke-opacity=\"0.3\"/>\n",
+                Self::MARGIN,
+                svg_color(grid)
+            ));
+            let anchor = match i {
+                0 => "start",
+                i if i == Self::TICKS => "end",
+                _ => "middle",
+            };
+            out.push_str(&format!(
+                "<text x=\"{px:.2}\" y=\"{:.2}\" text-anchor=\"{}\" fill=\"{}\">{}</text>\n",
+                axis_y + 18.0,
+                anchor,
+                svg_color(grid),
+                xml_escape(&tick_label(x))
+            ));
+        }
+        out.push_str(&format!(
+            "<line x1=\"{plot_left}\" y1=\"{axis_y:.2}\" x2=\"{:.2}\" y2=\"{axis_y:.2}\" stroke=\"{}\"/>\n",
+            plot_left + plot_width,
+            svg_color(grid)
+        ));
+
+        // レーンのラベル (y_offset はレーンの下端なので中央に合わせる)
+        for (y_offset, label) in &self.labels {
+            out.push_str(&format!(
+                "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"end\" dominant-baseline=\"middle\" fill=\"{}\">{}</text>\n",
+                plot_left - 8.0,
+                sy(y_offset + 0.5),
+                svg_color(grid),
+                xml_escape(label)
+            ));
+        }
+
+        out.push_str("<g clip-path=\"url(#plot)\">\n");
+        for (color, start, end, y_offset, value) in &self.boxes {
+            let (left, right) = (sx(*start), sx(*end));
+            let (top, bottom) = (sy(y_offset + 0.9), sy(y_offset + 0.1));
+            out.push_str(&format!(
+                "<rect x=\"{left:.2}\" y=\"{top:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{c}\" fill-opacity=\"0.25\" stroke=\"{c}\"/>\n",
+                right - left,
+                bottom - top,
+                c = svg_color(*color)
+            ));
+            out.push_str(&format!(
+                "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"middle\" dominant-baseline=\"middle\" fill=\"#ffffff\">{}</text>\n",
+                (left + right) / 2.0,
+                sy(y_offset + 0.5),
+                xml_escape(value)
+            ));
+        }
+        for (color, width, points) in &self.lines {
+            let path: Vec<String> = points
+                .iter()
+                .map(|[x, y]| format!("{:.2},{:.2}", sx(*x), sy(*y)))
+                .collect();
+            out.push_str(&format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+                path.join(" "),
+                svg_color(*color),
+                width
+            ));
+        }
+        out.push_str("</g>\n</svg>\n");
+        out
+    }
+}
+
+/// 区間の長さの分布を棒グラフの SVG 文字列にする (横軸はミリ秒)
+/// min_ms は最初のビンの下端、bin_width_ms はビンの幅
+fn histogram_svg(
+    title: &str,
+    min_ms: f64,
+    bin_width_ms: f64,
+    counts: &[usize],
+    bar: Color32,
+    background: Color32,
+    grid: Color32,
+) -> String {
+    const WIDTH: f64 = 800.0;
+    const HEIGHT: f64 = 320.0;
+    const LEFT: f64 = 60.0;
+    const RIGHT: f64 = 20.0;
+    const TOP: f64 = 30.0;
+    const BOTTOM: f64 = 40.0;
+    const TICKS: usize = 5;
+    let plot_width = WIDTH - LEFT - RIGHT;
+    let plot_height = HEIGHT - TOP - BOTTOM;
+    let max_count = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let bar_width = plot_width / counts.len().max(1) as f64;
+    let axis_y = TOP + plot_height;
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\" viewBox=\"0 0 {WIDTH} {HEIGHT}\" font-family=\"monospace\" font-size=\"12\">\n"
+    ));
+    out.push_str(&format!(
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        svg_color(background)
+    ));
+    out.push_str(&format!(
+        "<text x=\"{LEFT}\" y=\"{:.2}\" fill=\"{}\">{}</text>\n",
+        TOP - 10.0,
+        svg_color(grid),
+        xml_escape(title)
+    ));
+    // 縦軸 (件数) の目盛り
+    for i in 0..=TICKS {
+        let count = max_count * i as f64 / TICKS as f64;
+        let y = axis_y - plot_height * i as f64 / TICKS as f64;
+        out.push_str(&format!(
+            "<line x1=\"{LEFT}\" y1=\"{y:.2}\" x2=\"{:.2}\" y2=\"{y:.2}\" stroke=\"{}\" stroke-opacity=\"0.3\"/>\n",
+            LEFT + plot_width,
+            svg_color(grid)
+        ));
+        out.push_str(&format!(
+            "<text x=\"{:.2}\" y=\"{y:.2}\" text-anchor=\"end\" dominant-baseline=\"middle\" fill=\"{}\">{}</text>\n",
+            LEFT - 6.0,
+            svg_color(grid),
+            format!("{:.1}", count).trim_end_matches(".0")
+        ));
+    }
+    // 横軸 (区間の長さ) の目盛りはビンの境界に付ける
+    for i in 0..=TICKS {
+        let fraction = i as f64 / TICKS as f64;
+        let x = LEFT + plot_width * fraction;
+        let ms = min_ms + bin_width_ms * counts.len() as f64 * fraction;
+        out.push_str(&format!(
+            "<text x=\"{x:.2}\" y=\"{:.2}\" text-anchor=\"middle\" fill=\"{}\">{:.3} ms</text>\n",
+            axis_y + 18.0,
+            svg_color(grid),
+            ms
+        ));
+    }
+    for (i, &count) in counts.iter().enumerate() {
+        let height = plot_height * count as f64 / max_count;
+        out.push_str(&format!(
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{height:.2}\" fill=\"{}\"/>\n",
+            LEFT + bar_width * i as f64,
+            axis_y - height,
+            bar_width,
+            svg_color(bar)
+        ));
+    }
+    out.push_str(&format!(
+        "<line x1=\"{LEFT}\" y1=\"{axis_y:.2}\" x2=\"{:.2}\" y2=\"{axis_y:.2}\" stroke=\"{}\"/>\n",
+        LEFT + plot_width,
+        svg_color(grid)
+    ));
+    out.push_str("</svg>\n");
+    out
+}
+
+/// 区間の長さ [秒] を bins 個のビンに数える
+/// 戻り値は (最初のビンの下端, ビンの幅, 各ビンの件数)
+fn bin_durations(durations: &[f64], bins: usize) -> (f64, f64, Vec<usize>) {
+    let bins = bins.max(1);
+    let min_d = durations.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_d = durations.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    // すべて同じ長さの場合は 1 ms 幅のビンにまとめる
+    let bin_width = if max_d > min_d {
+        (max_d - min_d) / bins as f64
+    } else {
+        0.001
+    };
+    let mut counts = vec![0usize; bins];
+    for d in durations {
+        let bin = ((d - min_d) / bin_width) as usize;
+        counts[bin.min(bins - 1)] += 1;
+    }
+    (min_d, bin_width, counts)
+}
+
+fn svg_color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 元シグナルを変換して別レーンに表示する派生シグナル
+#[derive(Clone, Copy, PartialEq)]
+enum DerivedTransform {
+    Invert,
+    // 秒単位の時間シフト
+    Offset(f64),
+    // 指定したファイル番号の同名シグナルとの差分 (どちらか一方だけ High の区間)
+    Diff(usize),
+}
+
+struct DerivedSignal {
+    source: String,
+    transform: DerivedTransform,
+    visible: bool,
+}
+
+impl DerivedSignal {
+    fn label(&self, file_names: &[String]) -> String {
+        match self.transform {
+            DerivedTransform::Invert => format!("{} (inverted)", self.source),
+            DerivedTransform::Offset(dt) => format!("{} ({:+.3} s)", self.source, dt),
+            DerivedTransform::Diff(other) => format!(
+                "{} (diff vs {})",
+                self.source,
+                file_names.get(other).map(String::as_str).unwrap_or("?")
+            ),
+        }
+    }
+
+    /// 元シグナルの区間に変換を適用する
+    fn apply(
+        &self,
+        source: &SignalData,
+        min_t: f64,
+        max_t: f64,
+        files: &[FileData],
+    ) -> Vec<Interval> {
+        match self.transform {
+            DerivedTransform::Invert => invert_intervals(&source.on_intervals, min_t, max_t),
+            DerivedTransform::Offset(dt) => shift_intervals(&source.on_intervals, dt),
+            DerivedTransform::Diff(other) => {
+                let other = files
+                    .get(other)
+                    .and_then(|f| f.signals.get(&self.source))
+                    .map(|sig| &sig.on_intervals[..])
+                    .unwrap_or(&[]);
+                xor_intervals(&source.on_intervals, other)
+            }
+        }
+    }
 }
 
 struct GroupData {
     name: String,
     signals: Vec<String>,
+    // 設定されている場合、メンバーのシグナルはこの色相で明るさを変えて描画する
+    color: Option<Color32>,
+}
+
+// ログ一覧テーブルの列
+#[derive(Clone, Copy, PartialEq)]
+enum LogColumn {
+    Timestamp,
+    Kind,
+    Name,
+    Group,
+    Value,
+    Comment,
+}
+
+impl LogColumn {
+    const ALL: [LogColumn; 6] = [
+        LogColumn::Timestamp,
+        LogColumn::Kind,
+        LogColumn::Name,
+        LogColumn::Group,
+        LogColumn::Value,
+        LogColumn::Comment,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            LogColumn::Timestamp => "Timestamp",
+            LogColumn::Kind => "Kind",
+            LogColumn::Name => "Name",
+            LogColumn::Group => "Group",
+            LogColumn::Value => "Value",
+            LogColumn::Comment => "Comment",
+        }
+    }
+
+    fn width(self) -> f32 {
+        match self {
+            LogColumn::Timestamp => 200.0,
+            LogColumn::Kind => 70.0,
+            LogColumn::Name => 120.0,
+            LogColumn::Group => 90.0,
+            LogColumn::Value => 120.0,
+            LogColumn::Comment => 240.0,
+        }
+    }
+
+    /// 表示用の文字列を取り出す
+    fn cell_text(self, log: &LogEntry) -> String {
+        match self {
+            LogColumn::Timestamp => log.timestamp.clone(),
+            LogColumn::Kind => log.kind.clone(),
+            LogColumn::Name => log.name.clone(),
+            LogColumn::Group => log.group.clone().unwrap_or_default(),
+            LogColumn::Value => value_to_string(&log.value),
+            LogColumn::Comment => log.comment.clone().unwrap_or_default(),
+        }
+    }
+}
+
+// イベント数ヒストグラムウィンドウの状態
+struct HistogramState {
+    open: bool,
+    // ビンの幅 [秒]
+    bucket_width: f64,
+    // 集計対象のシグナル (ファイル番号, シグナル名)。None は全イベント
+    source: Option<(usize, String)>,
+    // true: 発生時刻ではなく High 区間の長さ (end - start) の分布を表示する
+    durations: bool,
+    // 区間長の分布のビン数
+    duration_bins: usize,
+}
+
+// ファイル結合ウィンドウの状態 (ファイル番号ごとの選択とオフセット [秒])
+#[derive(Default)]
+struct MergeFilesState {
+    open: bool,
+    entries: Vec<(bool, f64)>,
+}
+
+// 無通信区間 (ギャップ) 検出ウィンドウの状態
+struct GapFinderState {
+    open: bool,
+    // この長さ [秒] を超えてイベントがない区間をギャップとする
+    threshold: f64,
+    // 検出結果 (開始, 終了)
+    gaps: Vec<(f64, f64)>,
+}
+
+// シグナル表示切り替えパレットの状態
+#[derive(Default)]
+struct SignalPaletteState {
+    open: bool,
+    query: String,
+    // 候補一覧で選択中の行
+    selected: usize,
+}
+
+// ヒストグラムのビン数の上限 (これを超える場合は描画しない)
+const HISTOGRAM_MAX_BUCKETS: usize = 10_000;
+
+// ログ一覧テーブル (下部パネル) の状態
+struct LogTableState {
+    open: bool,
+    file_index: usize,
+    sort_column: LogColumn,
+    ascending: bool,
+    // 選択中の行 (logs のインデックス)
+    selected: Option<usize>,
+    // いずれかの列に含まれる文字列で行を絞り込む (大文字小文字は区別しない)
+    filter: String,
+    // true: プロットの表示範囲 (時刻) に入る行だけを出す
+    follow_plot: bool,
+    // ソート結果のキャッシュ: キーが変わったら作り直す
+    order_key: Option<LogOrderKey>,
+    order: Vec<usize>,
+}
+
+// ログ一覧の並び順を決める条件 (どれかが変わったら並べ直す)
+#[derive(PartialEq)]
+struct LogOrderKey {
+    file_index: usize,
+    log_count: usize,
+    sort_column: LogColumn,
+    ascending: bool,
+    filter: String,
+    window: Option<(f64, f64)>,
+}
+
+impl LogTableState {
+    fn new() -> Self {
+        Self {
+            open: false,
+            file_index: 0,
+            sort_column: LogColumn::Timestamp,
+            ascending: true,
+            selected: None,
+            filter: String::new(),
+            follow_plot: false,
+            order_key: None,
+            order: Vec::new(),
+        }
+    }
+
+    /// 現在のソート条件で logs の並び順を更新する
+    /// window を指定した場合は、その時刻範囲 [start, end] に入る行だけを残す
+    fn update_order(&mut self, logs: &[LogEntry], window: Option<(f64, f64)>) {
+        let window = window.filter(|_| self.follow_plot);
+        let key = LogOrderKey {
+            file_index: self.file_index,
+            log_count: logs.len(),
+            sort_column: self.sort_column,
+            ascending: self.ascending,
+            filter: self.filter.clone(),
+            window,
+        };
+        if self.order_key.as_ref() == Some(&key) {
+            return;
+        }
+        let filter = self.filter.to_lowercase();
+        let mut order: Vec<usize> = (0..logs.len())
+            .filter(|&i| {
+                window.is_none_or(|(start, end)| (start..=end).contains(&logs[i].timestamp_num))
+            })
+            .filter(|&i| {
+                filter.is_empty()
+                    || LogColumn::ALL
+                        .iter()
+                        .any(|c| c.cell_text(&logs[i]).to_lowercase().contains(&filter))
+            })
+            .collect();
+        let column = self.sort_column;
+        if column == LogColumn::Timestamp {
+            order.sort_by(|&a, &b| {
+                logs[a]
+                    .timestamp_num
+                    .partial_cmp(&logs[b].timestamp_num)
+                    .unwrap()
+            });
+        } else {
+            order.sort_by_cached_key(|&i| column.cell_text(&logs[i]));
+        }
+        if !self.ascending {
+            order.reverse();
+        }
+        self.order = order;
+        self.order_key = Some(key);
+    }
+}
+
+// バックグラウンド処理の結果
+enum TaskResult {
+    Conversion {
+        file_path: String,
+        // {output} で指定した一時ファイル (使わない場合は None)
+        output_file: Option<String>,
+        command: String,
+        output: std::io::Result<std::process::Output>,
+        // Cancel ボタンでプロセスを終了させた
+        cancelled: bool,
+    },
+    // フォルダ一括読み込み: (パス, 読み込み結果) の一覧
+    FolderLoaded {
+        folder: String,
+        results: Vec<(String, Result<FileData, String>)>,
+    },
+}
+
+// 実行中のバックグラウンド処理
+struct BackgroundTask {
+    label: String,
+    receiver: mpsc::Receiver<TaskResult>,
+    // 中断できる処理のみ Some (true にするとワーカーが処理を打ち切る)
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+// 左ペインでシグナルの行をドラッグしているときのペイロード
+struct SignalDrag {
+    file_index: usize,
+    group: String,
+    signal: String,
+}
+
+// 左ペインでグループの見出しをドラッグしているときのペイロード
+struct GroupDrag {
+    file_index: usize,
+    group: String,
+}
+
+// プロットのレーンクリックで左ペインのシグナルへ移動・強調表示する対象
+struct TreeFocus {
+    file_index: usize,
+    signal: String,
+    // 強調表示を終える時刻 (ui.input の time)
+    until: f64,
+    // 次の左ペイン描画で該当行までスクロールする
+    scroll_pending: bool,
+}
+
+// 左ペインのファイルごとのツリー描画で、ファイルをまたいで使う状態
+struct FileTreeContext<'a> {
+    // ピン留めしたシグナル (ファイル番号, シグナル名)
+    pinned: &'a mut BTreeSet<(usize, String)>,
+    // このグループだけをプロットする (ファイル番号, グループ名)
+    solo_group: &'a mut Option<(usize, String)>,
+    // 全ファイルの表示名 (派生シグナルのラベル用)
+    file_names: &'a [String],
+    // 全ファイルのシグナル名 (「Diff with」の候補用)
+    file_signals: &'a [HashSet<String>],
+}
+
+// 左ペインで Ctrl/Shift クリックにより複数選択したシグナル
+struct SignalSelection {
+    // (ファイル番号, シグナル名)
+    selected: BTreeSet<(usize, String)>,
+    // Shift クリックの範囲選択の起点
+    anchor: Option<(usize, String)>,
+    // 「Set color」で割り当てる色
+    color: Color32,
+}
+
+impl SignalSelection {
+    /// Ctrl/Shift 付きのクリックなら選択を更新して true を返す (表示の切り替えはしない)
+    /// rows は同じグループ内の行の並び (Shift の範囲選択に使う)
+    fn handle_click(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        key: (usize, String),
+        rows: &[(usize, String)],
+    ) -> bool {
+        if !response.clicked() {
+            return false;
+        }
+        let modifiers = ui.input(|i| i.modifiers);
+        if modifiers.shift {
+            let range = self.anchor.as_ref().and_then(|anchor| {
+                let a = rows.iter().position(|r| r == anchor)?;
+                let b = rows.iter().position(|r| *r == key)?;
+                Some((a.min(b), a.max(b)))
+            });
+            match range {
+                Some((lo, hi)) => self.selected.extend(rows[lo..=hi].iter().cloned()),
+                None => {
+                    self.selected.insert(key.clone());
+                    self.anchor = Some(key);
+                }
+            }
+            true
+        } else if modifiers.command {
+            if !self.selected.remove(&key) {
+                self.selected.insert(key.clone());
+            }
+            self.anchor = Some(key);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 選択中の行に背景色を付ける
+    fn paint_row(&self, ui: &egui::Ui, response: &egui::Response, key: &(usize, String)) {
+        if self.selected.contains(key) {
+            ui.painter().rect_filled(
+                response.rect.expand(1.0),
+                2.0,
+                Color32::from_rgba_unmultiplied(100, 150, 255, 48),
+            );
+        }
+    }
+}
+
+// エラーログの1件分
+struct ErrorLogEntry {
+    time: String,
+    message: String,
+    // エラーではなくお知らせ (空ファイルなど)
+    info: bool,
 }
 
 #[derive(Clone)]
@@ -97,22 +1165,56 @@ struct ConversionResult {
     stderr: String,
     ok: bool,
     json_file: Option<String>,
+    // json_file が一時ファイル (読み込み済みの内容は MyApp::converted_output にある)
+    temp_output: bool,
+    // 利用者が Cancel で中断した (失敗とは区別して表示する)
+    cancelled: bool,
 }
 
 // 各ファイルごとの状態をまとめる構造体
 struct FileData {
     file_name: String,
+    // 全ログの時刻に加えているオフセット [秒] (マニフェストで指定)
+    time_offset: f64,
+    // false の間は読み込んだまま中央ペインと時刻範囲の計算から外す
+    enabled: bool,
+    // 読み込み元のファイルパス
+    file_path: std::path::PathBuf,
+    // 読み込んだ時点のファイルの更新日時 (監視モードで変更の検出に使う)
+    modified: Option<std::time::SystemTime>,
+    // 変換スクリプトの出力から読み込んだ (file_path は JSON ではないので監視モードで読み直さない)
+    converted: bool,
+    // 表示名の編集中の入力内容
+    rename_buffer: Option<String>,
+    // 「Keep current」で確認済みの default_visibility との差分
+    accepted_visibility_diff: Vec<String>,
+    // 利用者が個別に設定したシグナルの描画色 (recalc の対象外)
+    signal_colors: HashMap<String, Color32>,
     logs: Vec<LogEntry>,
     signals: HashMap<String, SignalData>,
     groups: HashMap<String, GroupData>,
+    // ドラッグで並べ替えたグループの順序 (ここに無いグループは名前順で後ろに並ぶ)
+    group_order: Vec<String>,
     visibility_defaults: HashMap<(String, String), bool>,
+    // 派生シグナル (recalc の対象外)
+    derived: Vec<DerivedSignal>,
+    // 読み込み時に利用者へ知らせるメッセージ (追加時に表示して空にする)
+    load_messages: Vec<String>,
     min_time: f64,
     max_time: f64,
 }
 
 impl FileData {
+    /// 左ペイン・プロットで並べるグループ名の順序 (名前順に、ドラッグでの並べ替えを反映)
+    fn ordered_group_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.groups.keys().cloned().collect();
+        keys.sort();
+        order_like(&mut keys, &self.group_order);
+        keys
+    }
+
     /// 各ファイルのログやシグナル、グループなどを再計算する
-    fn recalc(&mut self) {
+    fn recalc(&mut self, settings: &UserSettings) {
         // min/max time
         self.min_time = self.logs.first().map(|x| x.timestamp_num).unwrap_or(0.0);
         self.max_time = self.logs.last().map(|x| x.timestamp_num).unwrap_or(10.0);
@@ -130,26 +1232,57 @@ impl FileData {
                 SignalData {
                     name: name.clone(),
                     on_intervals: vec![],
+                    state_samples: vec![],
+                    analog_samples: vec![],
+                    value_min: 0.0,
+                    value_max: 0.0,
+                    analog_gain: 1.0,
+                    analog_offset: 0.0,
+                    line_width: 2.0,
                     is_on: None,
                     visible: false,
                     color: Color32::WHITE, // 色は描画時にまとめて決めてもよい
+                    idle: false,
+                    total_high: 0.0,
+                    high_percent: None,
+                    period: None,
+                    first_edge: None,
+                    last_edge: None,
                 },
             );
         }
 
-        // グループ作成
-        self.groups.clear();
+        // グループ作成 (ユーザーが設定したグループ色と並び順は引き継ぐ)
+        let group_colors: HashMap<String, Color32> = self
+            .groups
+            .values()
+            .filter_map(|g| g.color.map(|c| (g.name.clone(), c)))
+            .collect();
+        let group_signal_orders: HashMap<String, Vec<String>> = self
+            .groups
+            .drain()
+            .map(|(name, g)| (name, g.signals))
+            .collect();
+        // group が無いログはグルーピングルール (正規表現) で最初に一致したグループに入れる
+        let rules = compile_grouping_rules(&settings.grouping_rules);
         let mut signal_to_group = HashMap::new();
+        let group_by_field = settings.group_by_field.trim();
         for log in &self.logs {
-            if let Some(grp) = &log.group {
-                if !grp.is_empty() {
-                    self.groups.entry(grp.clone()).or_insert_with(|| GroupData {
-                        name: grp.clone(),
-                        signals: Vec::new(),
-                    });
-                    if !signal_to_group.contains_key(&log.name) {
-                        signal_to_group.insert(log.name.clone(), grp.clone());
-                    }
+            let grp = match log_group_field(log, group_by_field) {
+                Some(grp) if !grp.is_empty() => Some(grp),
+                _ => rules
+                    .iter()
+                    .find(|(re, _)| re.is_match(&log.name))
+                    .map(|(_, group)| group.clone()),
+            };
+            if let Some(grp) = grp {
+                self.groups.entry(grp.clone()).or_insert_with(|| GroupData {
+                    name: grp.clone(),
+                    signals: Vec::new(),
+                    color: group_colors.get(&grp).copied(),
+                });
+                if !signal_to_group.contains_key(&log.name) {
+                    signal_to_group.insert(log.name.clone(), grp);
                 }
             }
         }
@@ -163,37 +1296,172 @@ impl FileData {
         }
         for g in self.groups.values_mut() {
             g.signals.sort();
+            if let Some(order) = group_signal_orders.get(&g.name) {
+                order_like(&mut g.signals, order);
+            }
         }
 
-        // デフォルト可視性を設定
+        // デフォルト可視性を設定 (default_visibility の指定が優先)
+        // グループに属さないシグナルは group を空文字列として引く
+        let mut explicit_visibility = HashSet::new();
         for (name, sig) in self.signals.iter_mut() {
-            let default = if let Some(group) = signal_to_group.get(name) {
-                self.visibility_defaults
-                    .get(&(group.clone(), name.clone()))
-                    .copied()
-                    .unwrap_or(false)
-            } else {
-                false
-            };
-            sig.visible = default;
+            let group = signal_to_group.get(name).cloned().unwrap_or_default();
+            let default = self
+                .visibility_defaults
+                .get(&(group, name.clone()))
+                .copied();
+            if default.is_some() {
+                explicit_visibility.insert(name.clone());
+            }
+            sig.visible = default.unwrap_or(settings.default_show_all);
         }
 
         // ログを走査し on_intervals を構築
         for log in &self.logs {
+            if settings.strict_kinds && !KNOWN_LOG_KINDS.contains(&log.kind.as_str()) {
+                continue;
+            }
             update_signal_data(&mut self.signals, log);
         }
         // interval をマージ
         for sig in self.signals.values_mut() {
+            // 区間を閉じたり捨てたりする前に、エッジがあったかを覚えておく
+            let has_edges = !sig.on_intervals.is_empty() || sig.is_on.is_some();
+            // OFF が来ないまま終わった ON は、設定があれば末尾まで High とみなす
+            if let Some(start) = sig.is_on.take().filter(|_| settings.extend_unclosed_on) {
+                sig.on_intervals.push(Interval {
+                    start,
+                    end: self.max_time,
+                });
+            }
             merge_on_intervals(sig);
+            if settings.debounce_ms > 0.0 {
+                let min_width = settings.debounce_ms / 1000.0;
+                sig.on_intervals.retain(|iv| iv.end - iv.start >= min_width);
+            }
+            sig.idle = !has_edges && sig.state_samples.is_empty() && sig.analog_samples.is_empty();
+            if sig.idle && settings.hide_idle_on_load && !explicit_visibility.contains(&sig.name) {
+                sig.visible = false;
+            }
+            if !sig.analog_samples.is_empty() {
+                sig.value_min = sig
+                    .analog_samples
+                    .iter()
+                    .map(|(_, v)| *v)
+                    .fold(f64::INFINITY, f64::min);
+                sig.value_max = sig
+                    .analog_samples
+                    .iter()
+                    .map(|(_, v)| *v)
+                    .fold(f64::NEG_INFINITY, f64::max);
+            }
+            sig.total_high = sig.on_intervals.iter().map(|iv| iv.end - iv.start).sum();
+            let span = self.max_time - self.min_time;
+            sig.high_percent = (span > 0.0).then(|| sig.total_high / span * 100.0);
+            let gaps: Vec<f64> = sig
+                .on_intervals
+                .windows(2)
+                .map(|w| w[1].start - w[0].start)
+                .collect();
+            sig.period = (!gaps.is_empty()).then(|| {
+                let n = gaps.len() as f64;
+                let mean = gaps.iter().sum::<f64>() / n;
+                let variance = gaps.iter().map(|g| (g - mean).powi(2)).sum::<f64>() / n;
+                (mean, variance.sqrt())
+            });
+            sig.first_edge = sig.on_intervals.first().map(|iv| iv.start);
+            sig.last_edge = sig.on_intervals.last().map(|iv| iv.end);
+        }
+    }
+
+    /// 全ログの時刻を offset 秒ずらして区間を計算し直す
+    fn shift_time(&mut self, offset: f64, settings: &UserSettings) {
+        for log in &mut self.logs {
+            log.timestamp_num += offset;
+        }
+        self.time_offset += offset;
+        self.recalc(settings);
+    }
+
+    /// シグナルが属するグループ名 (どのグループにも属さなければ空文字列)
+    fn group_of(&self, name: &str) -> String {
+        self.groups
+            .values()
+            .find(|g| g.signals.iter().any(|s| s == name))
+            .map(|g| g.name.clone())
+            .unwrap_or_default()
+    }
+
+    /// default_visibility の (group, name) が今のグループ構成に当てはまるか
+    /// (group が空文字列なら、どのグループにも属さないシグナルを指す)
+    fn is_in_group(&self, group: &str, name: &str) -> bool {
+        if group.is_empty() {
+            !self
+                .groups
+                .values()
+                .any(|g| g.signals.iter().any(|s| s == name))
+        } else {
+            self.groups
+                .get(group)
+                .is_some_and(|g| g.signals.iter().any(|s| s == name))
+        }
+    }
+
+    /// 現在の表示状態を default_visibility の形式で全シグナル分返す
+    fn current_visibility(&self) -> Vec<VisibilityEntry> {
+        let mut entries: Vec<VisibilityEntry> = self
+            .signals
+            .values()
+            .map(|sig| VisibilityEntry {
+                group: self.group_of(&sig.name),
+                name: sig.name.clone(),
+                visible: sig.visible,
+            })
+            .collect();
+        entries.sort_by(|a, b| (&a.group, &a.name).cmp(&(&b.group, &b.name)));
+        entries
+    }
+
+    /// default_visibility の指定と現在の表示状態が異なるシグナル名を返す
+    fn visibility_diff(&self) -> Vec<String> {
+        let mut diff: Vec<String> = self
+            .visibility_defaults
+            .iter()
+            .filter(|((group, name), _)| self.is_in_group(group, name))
+            .filter(|((_, name), visible)| {
+                self.signals
+                    .get(name)
+                    .is_some_and(|sig| sig.visible != **visible)
+            })
+            .map(|((_, name), _)| name.clone())
+            .collect();
+        diff.sort();
+        diff
+    }
+
+    /// 表示状態を default_visibility の指定に戻す
+    fn revert_visibility(&mut self) {
+        for ((group, name), visible) in &self.visibility_defaults {
+            if !self.is_in_group(group, name) {
+                continue;
+            }
+            if let Some(sig) = self.signals.get_mut(name) {
+                sig.visible = *visible;
+            }
         }
     }
 
     /// JSON の DataFile から FileData を生成する
-    fn from_data_file(data_file: DataFile, file_path: &str) -> Self {
+    fn from_data_file(data_file: DataFile, file_path: &str, settings: &UserSettings) -> Self {
         let mut logs = data_file.logs;
         for log in &mut logs {
             log.timestamp_num = parse_timestamp_to_f64(&log.timestamp);
         }
+        // ソート前に、直前のエントリより古い時刻のエントリを数えておく
+        let out_of_order = logs
+            .windows(2)
+            .filter(|w| w[1].timestamp_num < w[0].timestamp_num)
+            .count();
         logs.sort_by(|a, b| a.timestamp_num.partial_cmp(&b.timestamp_num).unwrap());
 
         let mut visibility_defaults = HashMap::new();
@@ -211,31 +1479,808 @@ impl FileData {
 
         let mut file_data = Self {
             file_name,
+            file_path: std::path::PathBuf::from(file_path),
+            modified: None,
+            converted: false,
+            rename_buffer: None,
+            accepted_visibility_diff: Vec::new(),
+            signal_colors: HashMap::new(),
             logs,
             signals: HashMap::new(),
             groups: HashMap::new(),
             visibility_defaults,
+            group_order: Vec::new(),
+            time_offset: 0.0,
+            enabled: true,
+            derived: Vec::new(),
+            load_messages: Vec::new(),
             min_time: 0.0,
             max_time: 10.0,
         };
-        file_data.recalc();
-        file_data
-    }
-}
-
-// ユーティリティ関数
-fn parse_timestamp_to_f64(ts: &str) -> f64 {
-    let replaced = ts.replace('T', " ").replace('Z', "");
-    if let Ok(ndt) = chrono::NaiveDateTime::parse_from_str(&replaced, "%Y-%m-%d %H:%M:%S%.3f") {
-        let epoch =
-            chrono::NaiveDateTime::parse_from_str("1970-01-01 00:00:00", "%Y-%m-%d %H:%M:%S")
-                .unwrap();
-        (ndt - epoch).num_milliseconds() as f64 / 1000.0
-    } else {
-        0.0
+        if file_data.logs.is_empty() {
+            file_data
+                .load_messages
+                .push(format!("{} contains 0 log entries.", file_data.file_name));
+        }
+        // 同じシグナル名が複数のグループに出てくる場合、最初のグループにしか入らないので知らせる
+        let group_by_field = settings.group_by_field.trim();
+        let mut signal_groups: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+        for log in &file_data.logs {
+            if let Some(group) = log_group_field(log, group_by_field).filter(|g| !g.is_empty()) {
+                signal_groups
+                    .entry(log.name.as_str())
+                    .or_default()
+                    .insert(group);
+            }
+        }
+        let conflicts: Vec<String> = signal_groups
+            .iter()
+            .filter(|(_, groups)| groups.len() > 1)
+            .map(|(name, groups)| {
+                format!(
+                    "{} ({})",
+                    name,
+                    groups.iter().cloned().collect::<Vec<_>>().join(", ")
+                )
+            })
+            .collect();
+        if !conflicts.is_empty() {
+            file_data.load_messages.push(format!(
+                "{}: signals appear in multiple groups and are shown only in the first: {}",
+                file_data.file_name,
+                conflicts.join("; ")
+            ));
+        }
+        if settings.strict_kinds {
+            let mut unknown_kinds: BTreeMap<&str, usize> = BTreeMap::new();
+            for log in &file_data.logs {
+                if !KNOWN_LOG_KINDS.contains(&log.kind.as_str()) {
+                    *unknown_kinds.entry(log.kind.as_str()).or_default() += 1;
+                }
+            }
+            if !unknown_kinds.is_empty() {
+                let list: Vec<String> = unknown_kinds
+                    .iter()
+                    .map(|(kind, count)| format!("\"{}\" x{}", kind, count))
+                    .collect();
+                file_data.load_messages.push(format!(
+                    "{}: unrecognized log types were ignored: {}",
+                    file_data.file_name,
+                    list.join(", ")
+                ));
+            }
+        }
+        if out_of_order > 0 {
+            file_data.load_messages.push(format!(
+                "{}: {} log entries were out of chronological order and have been sorted.",
+                file_data.file_name, out_of_order
+            ));
+        }
+        file_data.recalc(settings);
+        file_data
+    }
+}
+
+/// 子プロセスを実行して出力を集める (cancel が true になったらプロセスを終了させる)
+/// 戻り値の bool は中断したかどうか
+fn run_cancellable(
+    mut command: Command,
+    cancel: &AtomicBool,
+) -> (std::io::Result<std::process::Output>, bool) {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let mut child = match command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return (Err(e), false),
+    };
+    // パイプが詰まって子プロセスが止まらないよう、出力は別スレッドで読み続ける
+    let read_pipe = |pipe: Option<Box<dyn Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    };
+    let stdout_reader = read_pipe(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr_reader = read_pipe(child.stderr.take().map(|p| Box::new(p) as _));
+
+    let mut cancelled = false;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) => {}
+            Err(e) => break Err(e),
+        }
+        if cancel.load(Ordering::Relaxed) {
+            cancelled = true;
+            let _ = child.kill();
+            break child.wait();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    };
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+    let output = status.map(|status| std::process::Output {
+        status,
+        stdout,
+        stderr,
+    });
+    (output, cancelled)
+}
+
+/// list を reference に現れる順に並べ替える (reference に無い要素は元の順序のまま後ろへ)
+fn order_like(list: &mut [String], reference: &[String]) {
+    if reference.is_empty() {
+        return;
+    }
+    list.sort_by_key(|item| {
+        reference
+            .iter()
+            .position(|r| r == item)
+            .unwrap_or(usize::MAX)
+    });
+}
+
+/// from を to の位置へ移動する (下へ動かす場合は to の後ろ、上へ動かす場合は前に入る)
+fn move_item(list: &mut Vec<String>, from: &str, to: &str) {
+    let (Some(i), Some(j)) = (
+        list.iter().position(|x| x == from),
+        list.iter().position(|x| x == to),
+    ) else {
+        return;
+    };
+    let item = list.remove(i);
+    list.insert(j, item);
+}
+
+/// 数値をレーン内 (offset+0.1 〜 offset+0.9) の y 座標に正規化する
+/// 正規化した値にゲイン/オフセットを掛け、レーンからはみ出さないよう丸める
+fn analog_lane_y(sig: &SignalData, offset: f64, v: f64) -> f64 {
+    let low = offset + 0.1;
+    let high = offset + 0.9;
+    let range = sig.value_max - sig.value_min;
+    let p = if range > 0.0 {
+        (v - sig.value_min) / range
+    } else {
+        0.5
+    };
+    let p = (p * sig.analog_gain + sig.analog_offset).clamp(0.0, 1.0);
+    low + p * (high - low)
+}
+
+/// アナログ値が threshold を超えている区間ごとに点列を切り出す
+/// (交差する位置は線形補間した点で区切る)
+fn runs_above(samples: &[(f64, f64)], threshold: f64) -> Vec<Vec<(f64, f64)>> {
+    let mut runs = Vec::new();
+    let mut current: Vec<(f64, f64)> = Vec::new();
+    for (i, &(t, v)) in samples.iter().enumerate() {
+        if i > 0 {
+            let (pt, pv) = samples[i - 1];
+            if (pv > threshold) != (v > threshold) && v != pv {
+                let crossing = pt + (threshold - pv) / (v - pv) * (t - pt);
+                current.push((crossing, threshold));
+                if pv > threshold {
+                    runs.push(std::mem::take(&mut current));
+                }
+            }
+        }
+        if v > threshold {
+            current.push((t, v));
+        }
+    }
+    if !current.is_empty() {
+        runs.push(current);
+    }
+    runs
+}
+
+/// 時刻 t でのシグナルの状態を表示用の文字列にする (2つ目は High かどうか)
+/// 数値は前後のサンプルから線形補間し、文字列の状態値は直前の値を返す
+fn signal_state_at(sig: &SignalData, t: f64, unit: Option<&SignalUnit>) -> (String, bool) {
+    if !sig.analog_samples.is_empty() {
+        let i = sig.analog_samples.partition_point(|(st, _)| *st <= t);
+        let value = match (
+            i.checked_sub(1).map(|k| sig.analog_samples[k]),
+            sig.analog_samples.get(i),
+        ) {
+            (Some((t0, v0)), Some(&(t1, v1))) if t1 > t0 => v0 + (v1 - v0) * (t - t0) / (t1 - t0),
+            (Some((_, v0)), _) => v0,
+            (None, Some(&(_, v1))) => v1,
+            (None, None) => return ("-".to_string(), false),
+        };
+        let text = match unit {
+            Some(unit) => unit.format(value),
+            None => format!("{:.3}", value),
+        };
+        return (text, false);
+    }
+    if !sig.state_samples.is_empty() {
+        let i = sig.state_samples.partition_point(|(st, _)| *st <= t);
+        return match i.checked_sub(1) {
+            Some(k) => (sig.state_samples[k].1.clone(), false),
+            None => ("-".to_string(), false),
+        };
+    }
+    let i = sig.on_intervals.partition_point(|iv| iv.end <= t);
+    let high = sig.on_intervals.get(i).is_some_and(|iv| iv.start <= t);
+    (if high { "High" } else { "Low" }.to_string(), high)
+}
+
+/// ドラッグ中の行をドロップできる位置として、行の上端に線を引く
+fn paint_drop_marker(ui: &egui::Ui, response: &egui::Response) {
+    let rect = response.rect;
+    ui.painter().hline(
+        rect.x_range(),
+        rect.top(),
+        egui::Stroke::new(2.0, ui.visuals().selection.stroke.color),
+    );
+}
+
+/// JSON ファイルを読み込んで FileData を作る
+fn load_data_file(path: &str, settings: &UserSettings) -> Result<FileData, String> {
+    let data = fs::read_to_string(path).map_err(|e| format!("File read error: {}", e))?;
+    let data_file = if settings.tolerant_json {
+        parse_data_file(&strip_json_comments(&data))?
+    } else {
+        parse_data_file(&data)?
+    };
+    let mut file_data = FileData::from_data_file(data_file, path, settings);
+    file_data.modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+    Ok(file_data)
+}
+
+/// JSON ファイルがマニフェスト ({"files": [...]}) ならその内容を返す
+fn read_manifest(path: &str) -> Option<Manifest> {
+    let data = fs::read_to_string(path).ok()?;
+    serde_json::from_str::<Manifest>(&data).ok()
+}
+
+/// JSON 文字列を DataFile として解釈する
+/// { "logs": [...] } 形式のほか、ログエントリの配列だけの形式も受け付ける
+fn parse_data_file(data: &str) -> Result<DataFile, String> {
+    if let Ok(data_file) = serde_json::from_str::<DataFile>(data) {
+        return Ok(data_file);
+    }
+    serde_json::from_str::<Vec<LogEntry>>(data)
+        .map(|logs| DataFile {
+            logs,
+            default_visibility: None,
+        })
+        .map_err(|_| "Failed to parse JSON data as DataFile.".to_string())
+}
+
+/// 手で編集した JSON に含まれる // 行コメントと /* */ ブロックコメントを取り除く
+/// 文字列リテラル内の // などはそのまま残す
+fn strip_json_comments(data: &str) -> String {
+    let mut out = String::with_capacity(data.len());
+    let mut chars = data.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                // 改行は残して行番号がずれないようにする
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    if next == '\n' {
+                        out.push('\n');
+                    }
+                    prev = next;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// オブジェクト・配列の閉じ括弧の直前にある余分なカンマを取り除く
+fn strip_trailing_commas(data: &str) -> String {
+    let mut out = String::with_capacity(data.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    // 直前のカンマの位置 (その後に空白しか来ていない間だけ保持する)
+    let mut pending_comma: Option<usize> = None;
+    for c in data.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            out.push(c);
+            continue;
+        }
+        match c {
+            ',' => {
+                pending_comma = Some(out.len());
+                out.push(c);
+                continue;
+            }
+            '}' | ']' => {
+                if let Some(pos) = pending_comma {
+                    out.remove(pos);
+                }
+            }
+            '"' => in_string = true,
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            pending_comma = None;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// ディレクトリ内の .json ファイルを列挙する (recursive なら下位ディレクトリも)
+fn collect_json_files(
+    dir: &std::path::Path,
+    recursive: bool,
+    out: &mut Vec<std::path::PathBuf>,
+) -> std::io::Result<()> {
+    let mut entries: Vec<std::path::PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            if recursive {
+                collect_json_files(&path, recursive, out)?;
+            }
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+// ユーティリティ関数
+
+// 内部の時刻 (f64 の秒) の基準となるエポック秒
+// エポック秒のままの f64 では分解能が 0.2 マイクロ秒程度しかないので、
+// 最初に読んだ時刻の日付 (UTC の 0 時) からの経過秒で持ち、ナノ秒まで保てるようにする
+static TIME_ORIGIN: std::sync::OnceLock<i64> = std::sync::OnceLock::new();
+
+/// エポック秒とナノ秒を内部の時刻 (基準からの経過秒) にする
+/// 基準が未設定なら、この時刻の日付の 0 時 (UTC) を基準にする
+fn time_from_epoch(secs: i64, nanos: u32) -> f64 {
+    let origin = *TIME_ORIGIN.get_or_init(|| secs - secs.rem_euclid(86_400));
+    (secs - origin) as f64 + nanos as f64 / 1e9
+}
+
+/// 内部の時刻をエポック秒とナノ秒 (0〜999,999,999) に戻す
+fn time_to_epoch(x: f64) -> (i64, u32) {
+    let origin = TIME_ORIGIN.get().copied().unwrap_or(0);
+    let secs = x.floor();
+    let nanos = ((x - secs) * 1e9).round();
+    // 丸めで 1 秒に達したら繰り上げる
+    if nanos >= 1e9 {
+        (origin + secs as i64 + 1, 0)
+    } else {
+        (origin + secs as i64, nanos as u32)
+    }
+}
+
+/// タイムスタンプ文字列をエポック秒とナノ秒にする (解釈できなければ None)
+fn parse_timestamp_to_epoch(ts: &str) -> Option<(i64, u32)> {
+    // 小数点にカンマを使うロケールの "12:00:00,123" は "12:00:00.123" として扱う
+    let ts = &normalize_decimal_comma(ts);
+    // オフセット付き (RFC 3339) の場合は UTC に変換する
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
+        return Some((dt.timestamp(), dt.timestamp_subsec_nanos()));
+    }
+    // オフセットなしは UTC とみなす
+    let replaced = ts.replace('T', " ").replace('Z', "");
+    // %.f は小数部の桁数を問わず受け付ける (ナノ秒まで書かれたログも読める)
+    let ndt = chrono::NaiveDateTime::parse_from_str(&replaced, "%Y-%m-%d %H:%M:%S%.f").ok()?;
+    let utc = ndt.and_utc();
+    Some((utc.timestamp(), utc.timestamp_subsec_nanos()))
+}
+
+/// タイムスタンプ文字列を内部の時刻にする (解釈できなければ 0.0)
+fn parse_timestamp_to_f64(ts: &str) -> f64 {
+    parse_timestamp_to_epoch(ts)
+        .map(|(secs, nanos)| time_from_epoch(secs, nanos))
+        .unwrap_or(0.0)
+}
+
+/// 秒の直後の小数点のカンマ (":SS," の後に数字が続くもの) だけをドットに置き換える
+/// それ以外の位置のカンマはそのまま残す
+fn normalize_decimal_comma(ts: &str) -> String {
+    let chars: Vec<char> = ts.chars().collect();
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            let is_decimal = c == ','
+                && i >= 3
+                && chars[i - 3] == ':'
+                && chars[i - 2].is_ascii_digit()
+                && chars[i - 1].is_ascii_digit()
+                && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit());
+            if is_decimal {
+                '.'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// 内部の時刻を ISO 8601 (UTC) 形式の文字列にする
+fn format_iso(x: f64) -> String {
+    let (secs, nanos) = time_to_epoch(x);
+    match chrono::DateTime::from_timestamp(secs, nanos) {
+        Some(dt) => dt.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+        None => format!("{}", x),
+    }
+}
+
+/// 内部の時刻を "HH:MM:SS.fff" 形式 (小数桁数指定) の文字列にする
+fn format_time_of_day(x: f64, decimals: usize, timezone: DisplayTimezone) -> String {
+    let decimals = decimals.min(9);
+    let (secs, nanos) = time_to_epoch(x);
+    let dt = Utc.timestamp_opt(secs, nanos).unwrap();
+    let mut text = match timezone {
+        DisplayTimezone::Utc => dt.naive_utc().format("%H:%M:%S").to_string(),
+        DisplayTimezone::Local => dt
+            .with_timezone(&chrono::Local)
+            .format("%H:%M:%S")
+            .to_string(),
+    };
+    if decimals > 0 {
+        let frac = nanos / 10u32.pow(9 - decimals as u32);
+        text.push_str(&format!(".{:0width$}", frac, width = decimals));
+    }
+    text
+}
+
+/// 「Go to」の入力を内部の時刻に変換する
+/// ISO 8601 の日時のほか、HH:MM:SS(.fff) の時刻だけなら基準時刻と同じ日付として扱う
+fn parse_time_input(input: &str, reference: f64, timezone: DisplayTimezone) -> Option<f64> {
+    let input = input.trim();
+    if input.contains('-') {
+        let (secs, nanos) = parse_timestamp_to_epoch(input)?;
+        return Some(time_from_epoch(secs, nanos));
+    }
+    let time = chrono::NaiveTime::parse_from_str(input, "%H:%M:%S%.f").ok()?;
+    let (secs, nanos) = time_to_epoch(reference);
+    let reference = Utc.timestamp_opt(secs, nanos).unwrap();
+    let dt = match timezone {
+        DisplayTimezone::Utc => Utc.from_utc_datetime(&reference.date_naive().and_time(time)),
+        DisplayTimezone::Local => {
+            let date = reference.with_timezone(&chrono::Local).date_naive();
+            chrono::Local
+                .from_local_datetime(&date.and_time(time))
+                .earliest()?
+                .with_timezone(&Utc)
+        }
+    };
+    Some(time_from_epoch(dt.timestamp(), dt.timestamp_subsec_nanos()))
+}
+
+/// 基準時刻からの経過秒を "+MM:SS.fff" (1時間以上は "+H:MM:SS.fff") 形式にする
+fn format_relative_time(delta: f64, decimals: usize) -> String {
+    let decimals = decimals.min(9);
+    let sign = if delta < 0.0 { "-" } else { "+" };
+    let total_nanos = (delta.abs() * 1e9).round() as u64;
+    let secs = total_nanos / 1_000_000_000;
+    let (h, m, s) = (secs / 3600, (secs / 60) % 60, secs % 60);
+    let mut text = if h > 0 {
+        format!("{}{}:{:02}:{:02}", sign, h, m, s)
+    } else {
+        format!("{}{:02}:{:02}", sign, m, s)
+    };
+    if decimals > 0 {
+        let frac = (total_nanos % 1_000_000_000) / 10u64.pow(9 - decimals as u32);
+        text.push_str(&format!(".{:0width$}", frac, width = decimals));
+    }
+    text
+}
+
+/// JSON の値を表示用の文字列にする (文字列はクォートを外す)
+fn value_to_string(value: &serde_json::Value) -> String {
+    match value.as_str() {
+        Some(s) => s.to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// グループ分けに使うフィールドの値を取り出す (空なら group フィールド)
+/// group/name/type/value/comment/timestamp は extra に入らないので個別に読む
+fn log_group_field(log: &LogEntry, field: &str) -> Option<String> {
+    match field {
+        "" | "group" => log.group.clone(),
+        "name" => Some(log.name.clone()),
+        "type" => Some(log.kind.clone()),
+        "value" => Some(value_to_string(&log.value)),
+        "comment" => log.comment.clone(),
+        "timestamp" => Some(log.timestamp.clone()),
+        _ => log.extra.get(field).map(value_to_string),
+    }
+}
+
+/// カンマ区切りの拡張子リストを ".ext" 形式 (小文字) に正規化する
+fn parse_extension_list(text: &str) -> Vec<String> {
+    let mut extensions: Vec<String> = Vec::new();
+    for ext in text
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+    {
+        let ext = if ext.starts_with('.') {
+            ext
+        } else {
+            format!(".{}", ext)
+        };
+        if !extensions.contains(&ext) {
+            extensions.push(ext);
+        }
+    }
+    extensions
+}
+
+/// 複数のスクリプトが担当している拡張子と、そのスクリプト名一覧を返す
+fn ambiguous_extensions(scripts: &[ConversionScriptSetting]) -> BTreeMap<String, Vec<String>> {
+    let mut owners: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for script in scripts {
+        for ext in &script.extensions {
+            owners
+                .entry(ext.to_lowercase())
+                .or_default()
+                .push(script.name.clone());
+        }
+    }
+    owners.retain(|_, names| names.len() > 1);
+    owners
+}
+
+// 左ペインのシグナル行に添える小さな波形のサイズ [px]
+const SPARKLINE_SIZE: egui::Vec2 = egui::vec2(60.0, 10.0);
+
+/// シグナルの活動をキャプチャ全体 (span) にわたる小さな波形として描く
+/// ON/OFF 系は 1 ピクセル幅ごとに High の区間を含むかで上下させ、
+/// 状態値や数値のシグナルはサンプルのある位置に縦線を引く
+fn paint_sparkline(ui: &mut egui::Ui, sig: &SignalData, span: (f64, f64)) {
+    let (rect, _) = ui.allocate_exact_size(SPARKLINE_SIZE, egui::Sense::hover());
+    let (min_t, max_t) = span;
+    if !ui.is_rect_visible(rect) || max_t <= min_t {
+        return;
+    }
+    let columns = rect.width() as usize;
+    let column_of =
+        |t: f64| (((t - min_t) / (max_t - min_t) * columns as f64) as usize).min(columns - 1);
+    let stroke = egui::Stroke::new(1.0, ui.visuals().weak_text_color());
+    let painter = ui.painter();
+    let sample_times: Vec<f64> = if !sig.state_samples.is_empty() {
+        sig.state_samples.iter().map(|(t, _)| *t).collect()
+    } else {
+        sig.analog_samples.iter().map(|(t, _)| *t).collect()
+    };
+    if !sample_times.is_empty() {
+        let mut marked = vec![false; columns];
+        for t in sample_times {
+            marked[column_of(t)] = true;
+        }
+        for (c, _) in marked.iter().enumerate().filter(|(_, m)| **m) {
+            let x = rect.left() + c as f32 + 0.5;
+            painter.vline(x, rect.y_range(), stroke);
+        }
+        return;
+    }
+    let mut high = vec![false; columns];
+    for iv in &sig.on_intervals {
+        let (first, last) = (column_of(iv.start), column_of(iv.end));
+        for h in &mut high[first..=last] {
+            *h = true;
+        }
+    }
+    let mut points = Vec::new();
+    for (c, h) in high.iter().enumerate() {
+        let y = if *h { rect.top() } else { rect.bottom() };
+        points.push(egui::pos2(rect.left() + c as f32, y));
+        points.push(egui::pos2(rect.left() + c as f32 + 1.0, y));
+    }
+    painter.add(egui::Shape::line(points, stroke));
+}
+
+/// シグナルの表示チェックボックス (エッジのないシグナルはグレー表示)
+/// キャプチャ全体 (span) の活動の小さな波形と、
+/// ON/OFF 系のシグナルには High 時間の割合を右に添える (チェックボックスの Response を返す)
+fn signal_checkbox(
+    ui: &mut egui::Ui,
+    checked: &mut bool,
+    label: &str,
+    sig: &SignalData,
+    span: (f64, f64),
+) -> egui::Response {
+    ui.horizontal(|ui| {
+        let response = if sig.idle {
+            ui.checkbox(checked, egui::RichText::new(label).color(Color32::GRAY))
+                .on_hover_text("No edges in this capture")
+        } else {
+            ui.checkbox(checked, label)
+        };
+        paint_sparkline(ui, sig, span);
+        if sig.state_samples.is_empty() && sig.analog_samples.is_empty() {
+            let percent = match sig.high_percent {
+                Some(p) => format!("{:.1}%", p),
+                None => "N/A".to_string(),
+            };
+            let period = match sig.period {
+                Some((mean, stddev)) if mean > 0.0 => format!(
+                    "Period: {:.3} ms (σ {:.3} ms)\nFrequency: {:.3} Hz",
+                    mean * 1000.0,
+                    stddev * 1000.0,
+                    1.0 / mean
+                ),
+                _ => "Period: N/A\nFrequency: N/A".to_string(),
+            };
+            ui.weak(percent)
+                .on_hover_text(format!("High time / capture span\n{}", period));
+        }
+        response
+    })
+    .inner
+}
+
+/// ファイルのあるフォルダを OS のファイルマネージャで開く
+fn open_containing_folder(path: &std::path::Path) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => std::env::current_dir()?,
+    };
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(program).arg(dir).spawn().map(|_| ())
+}
+
+/// 環境変数 (名前, 値) の一覧を編集する
+fn env_editor(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, env: &mut Vec<(String, String)>) {
+    ui.push_id(id_salt, |ui| {
+        let mut remove_index = None;
+        for (i, (key, value)) in env.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(key)
+                        .hint_text("NAME")
+                        .desired_width(120.0),
+                );
+                ui.label("=");
+                ui.add(egui::TextEdit::singleline(value).hint_text("value"));
+                if ui.button("-").clicked() {
+                    remove_index = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove_index {
+            env.remove(i);
+        }
+        if ui.button("Add Variable").clicked() {
+            env.push((String::new(), String::new()));
+        }
+    });
+}
+
+/// 左ペインの行を強調表示し、スクロール待ちなら画面内へスクロールする
+fn highlight_tree_row(ui: &egui::Ui, response: &egui::Response, focus: &mut TreeFocus) {
+    ui.painter().rect_filled(
+        response.rect.expand(2.0),
+        2.0,
+        Color32::from_rgba_unmultiplied(255, 255, 0, 48),
+    );
+    if focus.scroll_pending {
+        response.scroll_to_me(Some(egui::Align::Center));
+        focus.scroll_pending = false;
+    }
+}
+
+/// 文字列から安定したパレット番号を求める (FNV-1a)
+fn stable_palette_index(key: &str, len: usize) -> usize {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in key.bytes() {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    (hash % len as u64) as usize
+}
+
+/// グループ色の明るさをメンバーの位置に応じてずらした色を返す
+fn group_member_color(base: Color32, index: usize, count: usize) -> Color32 {
+    let mut hsva = egui::ecolor::Hsva::from(base);
+    if count > 1 {
+        let t = index as f32 / (count - 1) as f32;
+        hsva.v = (1.0 - 0.55 * t).clamp(0.0, 1.0);
+    }
+    Color32::from(hsva)
+}
+
+/// query の文字が順に name に現れれば一致とし、スコアを返す (大文字小文字は区別しない)
+/// 部分文字列として含む場合と、連続して一致する文字が多い場合を上位にする
+fn fuzzy_score(name: &str, query: &str) -> Option<i32> {
+    let name = name.to_lowercase();
+    let query = query.to_lowercase();
+    if let Some(pos) = name.find(&query) {
+        return Some(1000 - pos as i32);
+    }
+    let mut score = 0;
+    let mut consecutive = false;
+    let mut chars = name.chars();
+    for q in query.chars() {
+        loop {
+            match chars.next() {
+                Some(c) if c == q => {
+                    score += if consecutive { 5 } else { 1 };
+                    consecutive = true;
+                    break;
+                }
+                Some(_) => consecutive = false,
+                None => return None,
+            }
+        }
+    }
+    Some(score)
+}
+
+/// ログの値が検索文字列に一致するか (完全一致 / 部分一致)
+fn value_matches(value: &serde_json::Value, query: &str, exact: bool) -> bool {
+    let text = value_to_string(value);
+    if exact {
+        text == query
+    } else {
+        text.contains(query)
     }
 }
 
+// update_signal_data が個別に解釈する type (これ以外は 0.2 秒の区間として描く)
+const KNOWN_LOG_KINDS: &[&str] = &["ONOFF", "PULSE", "STATE", "DURATION", "ANALOG", "ARROW"];
+
 fn update_signal_data(signals: &mut HashMap<String, SignalData>, log: &LogEntry) {
     let signal_name = &log.name;
     let time = log.timestamp_num;
@@ -265,6 +2310,37 @@ fn update_signal_data(signals: &mut HashMap<String, SignalData>, log: &LogEntry)
                 }
             }
         }
+        "STATE" => {
+            if let Some(sig) = signals.get_mut(signal_name) {
+                let val = value_to_string(&log.value);
+                // 値が変化したときだけサンプルを追加する
+                if sig.state_samples.last().is_none_or(|(_, v)| v != &val) {
+                    sig.state_samples.push((time, val));
+                }
+            }
+        }
+        "DURATION" => {
+            // value は継続時間 [ms]、または終了時刻を持つオブジェクト {"end": "..."}
+            let end = match &log.value {
+                serde_json::Value::Object(obj) => obj
+                    .get("end")
+                    .and_then(|v| v.as_str())
+                    .map(parse_timestamp_to_f64),
+                v => v.as_f64().map(|ms| time + ms / 1000.0),
+            };
+            if let (Some(end), Some(sig)) = (end, signals.get_mut(signal_name)) {
+                if end > time {
+                    sig.on_intervals.push(Interval { start: time, end });
+                }
+            }
+        }
+        "ANALOG" => {
+            if let Some(val) = log.value.as_f64() {
+                if let Some(sig) = signals.get_mut(signal_name) {
+                    sig.analog_samples.push((time, val));
+                }
+            }
+        }
         "ARROW" => {
             if let Some(sig) = signals.get_mut(signal_name) {
                 sig.on_intervals.push(Interval {
@@ -284,6 +2360,99 @@ fn update_signal_data(signals: &mut HashMap<String, SignalData>, log: &LogEntry)
     }
 }
 
+/// [min_t, max_t] の範囲で区間の補集合 (反転) を求める。区間はソート済みであること
+fn invert_intervals(intervals: &[Interval], min_t: f64, max_t: f64) -> Vec<Interval> {
+    let mut result = Vec::new();
+    let mut cursor = min_t;
+    for iv in intervals {
+        if iv.start > cursor {
+            result.push(Interval {
+                start: cursor,
+                end: iv.start.min(max_t),
+            });
+        }
+        cursor = cursor.max(iv.end);
+    }
+    if cursor < max_t {
+        result.push(Interval {
+            start: cursor,
+            end: max_t,
+        });
+    }
+    result
+}
+
+/// 2つの区間集合の和集合
+fn union_intervals(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    let mut all: Vec<Interval> = a.iter().chain(b).cloned().collect();
+    all.sort_by(|x, y| x.start.total_cmp(&y.start));
+    let mut result: Vec<Interval> = Vec::new();
+    for iv in all {
+        match result.last_mut() {
+            Some(last) if iv.start <= last.end => last.end = last.end.max(iv.end),
+            _ => result.push(iv),
+        }
+    }
+    result
+}
+
+/// 2つの区間集合の共通部分 (入力はそれぞれソート・マージ済みであること)
+fn intersect_intervals(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let start = a[i].start.max(b[j].start);
+        let end = a[i].end.min(b[j].end);
+        if start < end {
+            result.push(Interval { start, end });
+        }
+        if a[i].end < b[j].end {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    result
+}
+
+/// a から b と重なる部分を取り除いた区間 (入力はそれぞれソート・マージ済みであること)
+fn subtract_intervals(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    let (Some(first), Some(last)) = (a.first(), a.last()) else {
+        return Vec::new();
+    };
+    intersect_intervals(a, &invert_intervals(b, first.start, last.end))
+}
+
+/// どちらか一方だけが High の区間 (排他的論理和)
+fn xor_intervals(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    subtract_intervals(&union_intervals(a, b), &intersect_intervals(a, b))
+}
+
+/// 区間を dt 秒ずらす
+fn shift_intervals(intervals: &[Interval], dt: f64) -> Vec<Interval> {
+    intervals
+        .iter()
+        .map(|iv| Interval {
+            start: iv.start + dt,
+            end: iv.end + dt,
+        })
+        .collect()
+}
+
+/// 隙間が min_gap 未満の隣接区間を1つにまとめる (描画用の間引き)
+fn downsample_intervals(intervals: &[Interval], min_gap: f64) -> Vec<Interval> {
+    let mut result: Vec<Interval> = Vec::new();
+    for iv in intervals {
+        match result.last_mut() {
+            Some(last) if iv.start - last.end < min_gap => {
+                last.end = last.end.max(iv.end);
+            }
+            _ => result.push(iv.clone()),
+        }
+    }
+    result
+}
+
 fn merge_on_intervals(sig: &mut SignalData) {
     sig.on_intervals
         .sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
@@ -315,83 +2484,2158 @@ struct MyApp {
     open_files: Vec<FileData>,
     conversion_result: Option<ConversionResult>,
     error_dialog_message: Option<String>,
+    info_dialog_message: Option<String>,
+    error_log: Vec<ErrorLogEntry>,
+    error_log_open: bool,
+    about_open: bool,
+    // クリップボードからの読み込み待ち (次の貼り付けイベントを JSON として読む)
+    clipboard_open: bool,
+    // クリップボードから読み込んだ回数 (仮のファイル名 "clipboard-N" に使う)
+    clipboard_count: usize,
+    histogram: HistogramState,
+    gap_finder: GapFinderState,
+    merge_files: MergeFilesState,
+    signal_palette: SignalPaletteState,
     user_settings: UserSettings,
+    // 自動保存の変更検出用に、前フレームの設定を JSON にしたもの
+    settings_snapshot: String,
+    // 自動保存で書き込む予定の時刻 (ui.input の time)
+    settings_save_due: Option<f64>,
     settings_open: bool,
+    // Settings の「Reset to Defaults」の確認待ち
+    confirm_reset_settings: bool,
+    // Import Settings で読み込み、置き換えか取り込みかの確認待ちの設定
+    pending_settings_import: Option<UserSettings>,
     pending_import_file: Option<String>,
+    // 一時ファイルから読み込んだ変換結果 (変換結果ダイアログの OK で追加する)
+    converted_output: Option<Result<FileData, String>>,
+    // 起動引数で指定され、まだ読み込んでいないファイル
+    startup_paths: VecDeque<String>,
+    // 表示にした順の (ファイル番号, シグナル名) (上限を超えたときに古いものから隠す)
+    visible_order: VecDeque<(usize, String)>,
+    // 表示中のシグナル数が上限を超えているときのその数 (警告表示用)
+    visible_limit_exceeded: Option<usize>,
     pending_script_candidates: Option<Vec<ConversionScriptSetting>>,
+    log_table: LogTableState,
+    background_task: Option<BackgroundTask>,
+    // バックグラウンド処理中は操作を受け付けない
+    busy: bool,
+    // 中央ペインのタブ (None は全ファイルの重ね表示)
+    active_tab: Option<usize>,
+    // 時間軸を先頭からの経過時間で表示する
+    relative_time: bool,
+    // 同名グループをファイル横断でまとめて表示する
+    merge_groups: bool,
+    // エッジのないシグナルをチェック状態に関わらずプロットから除外する
+    hide_idle: bool,
+    // 次のフレームでプロットの表示範囲をリセットする
+    reset_plot_view: bool,
+    // 次のフレームで描画内容を SVG に書き出す
+    export_svg_pending: bool,
+    // High 区間を塗りつぶして描画する
+    fill_high: bool,
+    // 非表示のシグナルにもレーンを割り当てて位置を固定する
+    fixed_lanes: bool,
+    // compress_gap [s] より長い無通信区間を縮めて表示する
+    compress_idle: bool,
+    compress_gap: f64,
+    // y軸ラベルにグループ名を含める ("ファイル / グループ / シグナル")
+    show_group_labels: bool,
+    // 開いているファイルの変更を監視して読み直す
+    watch_files: bool,
+    // 前回ファイルの更新日時を確認した時刻 (ui.input の time)
+    last_watch_check: f64,
+    // 監視を始めてから読み直しで増えたログエントリ数
+    watch_new_events: usize,
+    // 直近の確認間隔で増えたエントリの毎秒の件数
+    watch_event_rate: Option<f64>,
+    // 前フレームのプロット表示範囲 (x_min, x_max, 幅[px])
+    plot_view: Option<(f64, f64, f32)>,
+    // 前フレームのプロット表示範囲を実際の時刻に直したもの (Compress Idle の縮約を戻す)
+    plot_time_range: Option<(f64, f64)>,
+    // 最後に設定したウィンドウタイトル
+    window_title: String,
+    // 前フレームのプロットの表示領域 (画像コピーで切り出す範囲)
+    plot_rect: Option<egui::Rect>,
+    // スクリーンショットの到着を待っている (届いたらプロット部分をコピーする)
+    copy_plot_pending: bool,
+    // プロットのレーンクリックで左ペインに強調表示するシグナル
+    tree_focus: Option<TreeFocus>,
+    // 左ペインで複数選択したシグナル
+    selection: SignalSelection,
+    // インスペクタのカーソル時刻 (プロットのクリックで設定)
+    cursor_time: Option<f64>,
+    inspector_open: bool,
+    // 設定ファイルを読めずデフォルトで起動した理由 (閉じるまでバナーで表示する)
+    settings_warning: Option<String>,
+    // 設定ファイルのあるディレクトリ (起動時に決め、スクリプトの相対パスの基準にする)
+    settings_dir: Option<std::path::PathBuf>,
+    // プロットの一番上に固定するシグナル (ファイル番号, シグナル名)
+    pinned: BTreeSet<(usize, String)>,
+    // このグループだけをプロットする (ファイル番号, グループ名)
+    solo_group: Option<(usize, String)>,
+    // 「Go to」の入力欄と、次のフレームで中央に表示する時刻
+    goto_input: String,
+    goto_invalid: bool,
+    goto_time: Option<f64>,
+    // 値検索 (一致したログをプロット上でマーク表示する)
+    value_search: String,
+    value_search_exact: bool,
+    // プロット上で強調表示する時刻 (ログ一覧で選択した行)
+    highlight_time: Option<f64>,
 }
 
 impl MyApp {
     fn new() -> Self {
-        let user_settings = Self::load_settings().unwrap_or_default();
-        Self {
+        let (user_settings, settings_error) = match Self::load_settings() {
+            Ok(settings) => (settings, None),
+            Err(e) => (UserSettings::default(), Some(e)),
+        };
+        let mut app = Self {
             open_files: Vec::new(),
             conversion_result: None,
             error_dialog_message: None,
+            info_dialog_message: None,
+            error_log: Vec::new(),
+            error_log_open: false,
+            about_open: false,
+            clipboard_open: false,
+            clipboard_count: 0,
+            histogram: HistogramState {
+                open: false,
+                bucket_width: 1.0,
+                source: None,
+                durations: false,
+                duration_bins: 20,
+            },
+            gap_finder: GapFinderState {
+                open: false,
+                threshold: 1.0,
+                gaps: Vec::new(),
+            },
+            signal_palette: SignalPaletteState::default(),
+            merge_files: MergeFilesState::default(),
+            settings_snapshot: serde_json::to_string(&user_settings).unwrap_or_default(),
+            settings_save_due: None,
             user_settings,
             settings_open: false,
+            confirm_reset_settings: false,
+            pending_settings_import: None,
             pending_import_file: None,
+            converted_output: None,
+            startup_paths: VecDeque::new(),
+            visible_order: VecDeque::new(),
+            visible_limit_exceeded: None,
             pending_script_candidates: None,
+            log_table: LogTableState::new(),
+            background_task: None,
+            busy: false,
+            active_tab: None,
+            relative_time: false,
+            merge_groups: false,
+            hide_idle: false,
+            reset_plot_view: false,
+            export_svg_pending: false,
+            fill_high: false,
+            fixed_lanes: false,
+            compress_idle: false,
+            compress_gap: 1.0,
+            show_group_labels: false,
+            watch_files: false,
+            last_watch_check: 0.0,
+            watch_new_events: 0,
+            watch_event_rate: None,
+            plot_view: None,
+            plot_time_range: None,
+            window_title: APP_TITLE.to_string(),
+            plot_rect: None,
+            copy_plot_pending: false,
+            tree_focus: None,
+            selection: SignalSelection {
+                selected: BTreeSet::new(),
+                anchor: None,
+                color: Color32::LIGHT_BLUE,
+            },
+            pinned: BTreeSet::new(),
+            solo_group: None,
+            goto_input: String::new(),
+            goto_invalid: false,
+            goto_time: None,
+            value_search: String::new(),
+            value_search_exact: false,
+            highlight_time: None,
+            settings_warning: None,
+            settings_dir: std::env::current_dir().ok(),
+            cursor_time: None,
+            inspector_open: false,
+        };
+        if let Some(e) = settings_error {
+            let message = format!("Could not load {}, using defaults: {}", SETTINGS_FILE, e);
+            eprintln!("{}", message);
+            app.error_log.push(ErrorLogEntry {
+                time: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                message: message.clone(),
+                info: false,
+            });
+            app.settings_warning = Some(message);
         }
+        app
     }
 
-    fn load_settings() -> Result<UserSettings, Box<dyn std::error::Error>> {
-        let settings_file = "user_settings.json";
-        if let Ok(content) = fs::read_to_string(settings_file) {
-            let settings: UserSettings = serde_json::from_str(&content)?;
-            Ok(settings)
-        } else {
-            Ok(UserSettings::default())
-        }
+    fn load_settings() -> Result<UserSettings, String> {
+        let content = match fs::read_to_string(SETTINGS_FILE) {
+            Ok(content) => content,
+            // 初回起動などファイルが無い場合はデフォルト
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(UserSettings::default())
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        Self::parse_settings(&content)
     }
 
+    /// 設定ファイルの内容を解釈する
+    /// 手で編集した設定ファイルのコメントや末尾のカンマは許容する
+    fn parse_settings(content: &str) -> Result<UserSettings, String> {
+        serde_json::from_str(content).or_else(|e| {
+            serde_json::from_str(&strip_trailing_commas(&strip_json_comments(content)))
+                .map_err(|_| e.to_string())
+        })
+    }
+
+    /// 設定をファイルに保存する
+    fn save_settings(settings: &UserSettings) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        fs::write(SETTINGS_FILE, content).map_err(|e| format!("Failed to save settings: {}", e))
+    }
+
+    /// エラーをログに追記し、最新のものをダイアログで表示する
     fn show_error_dialog(&mut self, message: &str) {
         eprintln!("{}", message);
+        self.error_log.push(ErrorLogEntry {
+            time: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+            message: message.to_owned(),
+            info: false,
+        });
         self.error_dialog_message = Some(message.to_owned());
     }
 
-    fn execute_conversion(&mut self, file_path: &str, script: ConversionScriptSetting) {
-        let command_str = format!(
-            "{} {} {}",
-            self.user_settings.python_path, script.script_path, file_path
-        );
-        let output = Command::new(&self.user_settings.python_path)
-            .arg(&script.script_path)
-            .arg(file_path)
-            .output();
-        let (stdout, stderr, ok, json_file) = match output {
-            Ok(o) => {
-                let ok = o.status.success();
-                let stdout = String::from_utf8_lossy(&o.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&o.stderr).to_string();
-                let json_file = if ok {
-                    Some(
-                        std::path::Path::new(file_path)
-                            .with_extension("json")
-                            .to_string_lossy()
-                            .to_string(),
-                    )
-                } else {
-                    None
-                };
-                (stdout, stderr, ok, json_file)
-            }
-            Err(e) => {
+    /// お知らせをログに追記し、ダイアログで表示する
+    fn show_info_dialog(&mut self, message: &str) {
+        eprintln!("{}", message);
+        self.error_log.push(ErrorLogEntry {
+            time: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+            message: message.to_owned(),
+            info: true,
+        });
+        self.info_dialog_message = Some(message.to_owned());
+    }
+
+    /// 読み込んだファイルを追加し、読み込み時のお知らせを表示する
+    fn add_file(&mut self, mut file_data: FileData) {
+        for message in std::mem::take(&mut file_data.load_messages) {
+            self.show_info_dialog(&message);
+        }
+        // 同名のファイルが開かれていれば連番を付けて区別する
+        let base_name = file_data.file_name.clone();
+        let mut counter = 2;
+        while self
+            .open_files
+            .iter()
+            .any(|f| f.file_name == file_data.file_name)
+        {
+            file_data.file_name = format!("{} ({})", base_name, counter);
+            counter += 1;
+        }
+        let before = (!self.open_files.is_empty()).then(|| self.global_time_range());
+        self.open_files.push(file_data);
+        let (min_t, max_t) = self.global_time_range();
+        let fit = match self.user_settings.view_on_load {
+            ViewOnLoad::Keep => false,
+            ViewOnLoad::Fit => true,
+            ViewOnLoad::FitIfExtended => match before {
+                Some((before_min, before_max)) => min_t < before_min || max_t > before_max,
+                None => true,
+            },
+        };
+        if fit {
+            self.reset_plot_view = true;
+        }
+    }
+
+    /// 相対パスのスクリプトを基準ディレクトリに対して解決する
+    /// 基準は script_base_dir → 設定ファイルのあるディレクトリ → 実行ファイルのディレクトリの順に試す
+    fn resolve_script_path(&self, script_path: &str) -> std::path::PathBuf {
+        let path = std::path::Path::new(script_path);
+        if path.is_absolute() {
+            return path.to_path_buf();
+        }
+        let mut bases = Vec::new();
+        if !self.user_settings.script_base_dir.is_empty() {
+            bases.push(std::path::PathBuf::from(
+                &self.user_settings.script_base_dir,
+            ));
+        }
+        if let Some(dir) = &self.settings_dir {
+            bases.push(dir.clone());
+        }
+        if let Ok(exe) = std::env::current_exe() {
+            if let Some(dir) = exe.parent() {
+                bases.push(dir.to_path_buf());
+            }
+        }
+        bases
+            .iter()
+            .map(|base| base.join(path))
+            .find(|candidate| candidate.exists())
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// JSON ファイルを開いて open_files に追加する
+    fn open_json_file(&mut self, path: &str) {
+        if let Some(manifest) = read_manifest(path) {
+            self.open_manifest(path, manifest);
+            return;
+        }
+        match load_data_file(path, &self.user_settings) {
+            Ok(file_data) => self.add_file(file_data),
+            Err(e) => self.show_error_dialog(&e),
+        }
+    }
+
+    /// 結合するファイルとオフセットを選ぶ
+    fn show_merge_files(&mut self, ui: &mut egui::Ui) {
+        let entries = &mut self.merge_files.entries;
+        entries.resize(self.open_files.len(), (false, 0.0));
+        egui::Grid::new("merge_files_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (file_data, (selected, offset)) in
+                    self.open_files.iter().zip(entries.iter_mut())
+                {
+                    ui.checkbox(selected, &file_data.file_name);
+                    ui.add_enabled(
+                        *selected,
+                        egui::DragValue::new(offset)
+                            .speed(0.001)
+                            .prefix("offset ")
+                            .suffix(" s"),
+                    );
+                    ui.end_row();
+                }
+            });
+        ui.weak("Signals that exist in more than one file are prefixed with the file name.");
+        let chosen: Vec<(usize, f64)> = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (selected, _))| *selected)
+            .map(|(i, (_, offset))| (i, *offset))
+            .collect();
+        if ui
+            .add_enabled(chosen.len() > 1, egui::Button::new("Merge"))
+            .clicked()
+        {
+            self.merge_files = MergeFilesState::default();
+            self.merge_open_files(&chosen);
+        }
+    }
+
+    /// 選んだファイルのログを (オフセットを加えて) 1つのファイルにまとめ、元のファイルと置き換える
+    fn merge_open_files(&mut self, chosen: &[(usize, f64)]) {
+        // 2つ以上のファイルにある同名シグナルはファイル名を前に付けて区別する
+        let mut owners: HashMap<&str, usize> = HashMap::new();
+        for (i, _) in chosen {
+            for name in self.open_files[*i].signals.keys() {
+                *owners.entry(name.as_str()).or_default() += 1;
+            }
+        }
+        let collisions: HashSet<String> = owners
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        let names: Vec<String> = chosen
+            .iter()
+            .map(|(i, _)| self.open_files[*i].file_name.clone())
+            .collect();
+        let mut removed: Vec<(usize, f64)> = chosen.to_vec();
+        removed.sort_by_key(|r| std::cmp::Reverse(r.0));
+        let mut logs = Vec::new();
+        let mut visible = HashMap::new();
+        for (i, offset) in &removed {
+            let file_data = self.open_files.remove(*i);
+            let rename = |name: &str| {
+                if collisions.contains(name) {
+                    format!("{}/{}", file_data.file_name, name)
+                } else {
+                    name.to_string()
+                }
+            };
+            for sig in file_data.signals.values() {
+                visible.insert(rename(&sig.name), sig.visible);
+            }
+            // 時刻は文字列から読み直されるので、ずらした時刻を書き戻しておく
+            let shifted = *offset != 0.0 || file_data.time_offset != 0.0;
+            for mut log in file_data.logs {
+                log.name = rename(&log.name);
+                log.timestamp_num += offset;
+                if shifted {
+                    log.timestamp = format_iso(log.timestamp_num);
+                }
+                logs.push(log);
+            }
+        }
+        // ファイルごとに並んだままだと時刻の逆転として報告されるので、時刻順に並べ直す
+        logs.sort_by(|a, b| a.timestamp_num.total_cmp(&b.timestamp_num));
+        let removed: Vec<usize> = removed.iter().map(|(i, _)| *i).collect();
+        self.forget_removed_files(&removed);
+
+        let data_file = DataFile {
+            logs,
+            default_visibility: None,
+        };
+        let mut merged = FileData::from_data_file(data_file, "merged", &self.user_settings);
+        merged.file_name = names.join(" + ");
+        for (name, sig) in merged.signals.iter_mut() {
+            if let Some(v) = visible.get(name) {
+                sig.visible = *v;
+            }
+        }
+        self.add_file(merged);
+    }
+
+    /// ファイルを取り除いたあと、ファイル番号で持っている状態を詰め直す
+    /// (removed は取り除く前の番号)
+    fn forget_removed_files(&mut self, removed: &[usize]) {
+        let remap = |i: usize| -> Option<usize> {
+            (!removed.contains(&i)).then(|| i - removed.iter().filter(|r| **r < i).count())
+        };
+        self.pinned = std::mem::take(&mut self.pinned)
+            .into_iter()
+            .filter_map(|(i, name)| remap(i).map(|i| (i, name)))
+            .collect();
+        self.solo_group = self
+            .solo_group
+            .take()
+            .and_then(|(i, group)| remap(i).map(|i| (i, group)));
+        for file_data in &mut self.open_files {
+            file_data.derived.retain_mut(|d| match &mut d.transform {
+                DerivedTransform::Diff(other) => match remap(*other) {
+                    Some(i) => {
+                        *other = i;
+                        true
+                    }
+                    None => false,
+                },
+                _ => true,
+            });
+        }
+        self.active_tab = None;
+        self.tree_focus = None;
+        self.selection.selected.clear();
+        self.selection.anchor = None;
+        self.visible_order.clear();
+        self.histogram.source = None;
+        self.log_table.file_index = 0;
+        self.log_table.selected = None;
+        self.log_table.order_key = None;
+    }
+
+    /// マニフェストに並んだファイルを、オフセットと表示シグナルを適用して開く
+    fn open_manifest(&mut self, manifest_path: &str, manifest: Manifest) {
+        let base = std::path::Path::new(manifest_path)
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default();
+        for entry in manifest.files {
+            let path = base.join(&entry.path);
+            let path_str = path.to_string_lossy().to_string();
+            match load_data_file(&path_str, &self.user_settings) {
+                Ok(mut file_data) => {
+                    if entry.offset != 0.0 {
+                        file_data.shift_time(entry.offset, &self.user_settings);
+                    }
+                    if let Some(visible) = &entry.visible {
+                        for sig in file_data.signals.values_mut() {
+                            sig.visible = visible.contains(&sig.name);
+                        }
+                    }
+                    self.add_file(file_data);
+                }
+                Err(e) => self.show_error_dialog(&format!("{}: {}", path_str, e)),
+            }
+        }
+    }
+
+    /// 貼り付けられた JSON 文字列を "clipboard-N" という名前のファイルとして読み込む
+    fn open_json_text(&mut self, text: &str) {
+        let text = if self.user_settings.tolerant_json {
+            strip_json_comments(text)
+        } else {
+            text.to_string()
+        };
+        match parse_data_file(&text) {
+            Ok(data_file) => {
+                self.clipboard_count += 1;
+                let name = format!("clipboard-{}", self.clipboard_count);
+                let file_data = FileData::from_data_file(data_file, &name, &self.user_settings);
+                self.add_file(file_data);
+            }
+            Err(e) => self.show_error_dialog(&format!("Clipboard: {}", e)),
+        }
+    }
+
+    /// JSON はそのまま開き、それ以外は拡張子に対応する変換スクリプトで取り込む
+    fn import_file(&mut self, path_str: String) {
+        if path_str.to_lowercase().ends_with(".json") {
+            self.open_json_file(&path_str);
+        } else {
+            let ext = std::path::Path::new(&path_str)
+                .extension()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_lowercase();
+            let ext_with_dot = if !ext.is_empty() {
+                format!(".{}", ext)
+            } else {
+                "".to_string()
+            };
+            let candidates: Vec<_> = self
+                .user_settings
+                .conversion_scripts
+                .iter()
+                .cloned()
+                .filter(|script| {
+                    script
+                        .extensions
+                        .iter()
+                        .any(|e| e.to_lowercase() == ext_with_dot)
+                })
+                .collect();
+            if candidates.is_empty() {
+                self.show_error_dialog(&format!(
+                    "拡張子 {} に対応する変換スクリプトが設定されていません。",
+                    ext_with_dot
+                ));
+            } else if candidates.len() == 1 {
+                self.execute_conversion(&path_str, candidates[0].clone());
+            } else {
+                self.pending_import_file = Some(path_str);
+                self.pending_script_candidates = Some(candidates);
+            }
+        }
+    }
+
+    /// 一時ファイルに出力された変換結果を、変換元のファイルとして読み込んで一時ファイルを消す
+    fn load_temp_output(&self, json_path: &str, source_file: &str) -> Result<FileData, String> {
+        let result = load_data_file(json_path, &self.user_settings);
+        let _ = fs::remove_file(json_path);
+        let mut file_data = result?;
+        let source = std::path::Path::new(source_file);
+        if let Some(stem) = source.file_stem() {
+            file_data.file_name = stem.to_string_lossy().to_string();
+        }
+        file_data.file_path = source.to_path_buf();
+        file_data.modified = fs::metadata(source).and_then(|m| m.modified()).ok();
+        file_data.converted = true;
+        Ok(file_data)
+    }
+
+    /// フォルダ内の JSON ファイルをバックグラウンドでまとめて読み込む
+    fn open_folder(&mut self, recursive: bool) {
+        if self.busy {
+            self.show_error_dialog("Another operation is already running.");
+            return;
+        }
+        let Some(dir) = FileDialog::new().pick_folder() else {
+            return;
+        };
+        let settings = self.user_settings.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let folder = dir.to_string_lossy().to_string();
+            let mut paths = Vec::new();
+            let results = match collect_json_files(&dir, recursive, &mut paths) {
+                Ok(()) => paths
+                    .iter()
+                    .map(|path| {
+                        let path_str = path.to_string_lossy().to_string();
+                        let result = load_data_file(&path_str, &settings);
+                        (path_str, result)
+                    })
+                    .collect(),
+                Err(e) => vec![(folder.clone(), Err(format!("Failed to read folder: {}", e)))],
+            };
+            let _ = sender.send(TaskResult::FolderLoaded { folder, results });
+        });
+        self.background_task = Some(BackgroundTask {
+            label: "Loading folder...".to_string(),
+            receiver,
+            cancel: None,
+        });
+        self.busy = true;
+    }
+
+    /// 変換スクリプトをバックグラウンドスレッドで実行する
+    fn execute_conversion(&mut self, file_path: &str, script: ConversionScriptSetting) {
+        if self.busy {
+            self.show_error_dialog("Another operation is already running.");
+            return;
+        }
+        let script_path = self.resolve_script_path(&script.script_path);
+        // 全体設定 → スクリプト個別の順に適用する (同名はスクリプト側が優先)
+        let env: Vec<(String, String)> = self
+            .user_settings
+            .script_env
+            .iter()
+            .chain(&script.env)
+            .filter(|(key, _)| !key.is_empty())
+            .cloned()
+            .collect();
+        let env_prefix: String = env
+            .iter()
+            .map(|(key, value)| format!("{}={} ", key, value))
+            .collect();
+        // {output} があれば一時ファイルに書き出させる
+        let output_file = script.uses_output_file().then(|| {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or(0);
+            std::env::temp_dir()
+                .join(format!(
+                    "log_analyzer_{}_{}.json",
+                    std::process::id(),
+                    nanos
+                ))
+                .to_string_lossy()
+                .to_string()
+        });
+        let args = script.expand_arguments(file_path, output_file.as_deref().unwrap_or(""));
+        let command_str = format!(
+            "{}{} {} {}",
+            env_prefix,
+            self.user_settings.python_path,
+            script_path.display(),
+            args.join(" ")
+        );
+        let python_path = self.user_settings.python_path.clone();
+        let file_path = file_path.to_string();
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cancel_flag = cancel.clone();
+        std::thread::spawn(move || {
+            let mut command = Command::new(&python_path);
+            command.arg(&script_path).args(&args).envs(env);
+            let (output, cancelled) = run_cancellable(command, &cancel_flag);
+            let _ = sender.send(TaskResult::Conversion {
+                file_path,
+                output_file,
+                command: command_str,
+                output,
+                cancelled,
+            });
+        });
+        self.background_task = Some(BackgroundTask {
+            label: "Converting...".to_string(),
+            receiver,
+            cancel: Some(cancel),
+        });
+        self.busy = true;
+    }
+
+    /// 変換プロセスの終了結果から ConversionResult を作る
+    fn finish_conversion(
+        &mut self,
+        file_path: &str,
+        output_file: Option<String>,
+        command_str: String,
+        output: std::io::Result<std::process::Output>,
+        cancelled: bool,
+    ) {
+        let (stdout, stderr, ok, json_file) = match output {
+            Ok(o) => {
+                let ok = o.status.success();
+                let stdout = String::from_utf8_lossy(&o.stdout).to_string();
+                let stderr = String::from_utf8_lossy(&o.stderr).to_string();
+                let json_file = if ok {
+                    Some(output_file.clone().unwrap_or_else(|| {
+                        std::path::Path::new(file_path)
+                            .with_extension("json")
+                            .to_string_lossy()
+                            .to_string()
+                    }))
+                } else {
+                    None
+                };
+                (stdout, stderr, ok, json_file)
+            }
+            Err(e) => {
                 self.show_error_dialog(&format!("Failed to execute the conversion script: {}", e));
                 ("".to_string(), "".to_string(), false, None)
             }
         };
+        // 中断した場合は途中までの出力を読み込まない
+        let (ok, json_file) = if cancelled {
+            (false, None)
+        } else {
+            (ok, json_file)
+        };
+        // 一時ファイルは OK を待たずに読み込んで消す (失敗時に残ったものも片付ける)
+        if let Some(tmp) = &output_file {
+            if ok {
+                self.converted_output = Some(self.load_temp_output(tmp, file_path));
+            } else {
+                let _ = fs::remove_file(tmp);
+            }
+        }
         self.conversion_result = Some(ConversionResult {
             command: command_str,
             stdout,
             stderr,
             ok,
             json_file,
+            temp_output: output_file.is_some(),
+            cancelled,
+        });
+    }
+
+    /// 監視モードで、一定間隔ごとに更新日時が変わったファイルを読み直す
+    /// (ネットワークドライブなど変更通知が当てにならない環境向けにポーリングで確認する)
+    fn poll_watched_files(&mut self, ctx: &egui::Context) {
+        if !self.watch_files || self.open_files.is_empty() {
+            return;
+        }
+        let interval = std::time::Duration::from_millis(self.user_settings.watch_interval_ms);
+        ctx.request_repaint_after(interval);
+        let now = ctx.input(|i| i.time);
+        if now - self.last_watch_check < interval.as_secs_f64() {
+            return;
+        }
+        let elapsed = now - self.last_watch_check;
+        let first_check = self.last_watch_check == 0.0;
+        self.last_watch_check = now;
+        let mut new_events = 0;
+        for i in 0..self.open_files.len() {
+            if self.open_files[i].converted {
+                continue;
+            }
+            let path = self.open_files[i].file_path.clone();
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            if modified.is_none() || modified == self.open_files[i].modified {
+                continue;
+            }
+            // 書き込み途中などで読めない場合は次の確認で再試行する
+            let Ok(mut reloaded) = load_data_file(&path.to_string_lossy(), &self.user_settings)
+            else {
+                continue;
+            };
+            let old = &mut self.open_files[i];
+            if old.time_offset != 0.0 {
+                reloaded.shift_time(old.time_offset, &self.user_settings);
+            }
+            new_events += reloaded.logs.len().saturating_sub(old.logs.len());
+            reloaded.file_name = std::mem::take(&mut old.file_name);
+            reloaded.derived = std::mem::take(&mut old.derived);
+            reloaded.signal_colors = std::mem::take(&mut old.signal_colors);
+            reloaded.group_order = std::mem::take(&mut old.group_order);
+            reloaded.enabled = old.enabled;
+            reloaded.load_messages.clear();
+            for (name, sig) in reloaded.signals.iter_mut() {
+                if let Some(old_sig) = old.signals.get(name) {
+                    sig.visible = old_sig.visible;
+                    sig.analog_gain = old_sig.analog_gain;
+                    sig.analog_offset = old_sig.analog_offset;
+                    sig.line_width = old_sig.line_width;
+                }
+            }
+            for (name, group) in reloaded.groups.iter_mut() {
+                if let Some(old_group) = old.groups.get(name) {
+                    group.color = old_group.color;
+                    order_like(&mut group.signals, &old_group.signals);
+                }
+            }
+            *old = reloaded;
+            // 件数が同じでも中身が変わっているので、ログ一覧の並び順を作り直す
+            self.log_table.order_key = None;
+        }
+        // 初回は比較対象の間隔がないので件数のみ数える
+        self.watch_new_events += new_events;
+        if !first_check && elapsed > 0.0 {
+            self.watch_event_rate = Some(new_events as f64 / elapsed);
+        }
+    }
+
+    /// バックグラウンド処理の完了を確認する
+    fn poll_background_task(&mut self, ctx: &egui::Context) {
+        let Some(task) = &self.background_task else {
+            self.busy = false;
+            return;
+        };
+        match task.receiver.try_recv() {
+            Ok(result) => {
+                self.background_task = None;
+                self.busy = false;
+                match result {
+                    TaskResult::Conversion {
+                        file_path,
+                        output_file,
+                        command,
+                        output,
+                        cancelled,
+                    } => {
+                        self.finish_conversion(&file_path, output_file, command, output, cancelled)
+                    }
+                    TaskResult::FolderLoaded { folder, results } => {
+                        if results.is_empty() {
+                            self.show_error_dialog(&format!("No JSON files found in {}", folder));
+                        }
+                        for (path, result) in results {
+                            match result {
+                                Ok(file_data) => self.add_file(file_data),
+                                Err(e) => self.show_error_dialog(&format!("{}: {}", path, e)),
+                            }
+                        }
+                    }
+                }
+            }
+            Err(mpsc::TryRecvError::Empty) => {
+                ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            }
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.background_task = None;
+                self.busy = false;
+                self.show_error_dialog("Background task terminated unexpectedly.");
+            }
+        }
+    }
+
+    /// 表示中のシグナル数を max_visible_signals 以内に抑える
+    /// 表示にした順を覚えておき、上限を超えたら警告するか古いものから非表示にする
+    /// 非表示にしたシグナルがあれば true を返す
+    fn enforce_visible_limit(&mut self) -> bool {
+        let mut visible = Vec::new();
+        for (file_index, file_data) in self.open_files.iter().enumerate() {
+            let mut names: Vec<&String> = file_data
+                .signals
+                .values()
+                .filter(|sig| sig.visible)
+                .map(|sig| &sig.name)
+                .collect();
+            names.sort();
+            visible.extend(names.into_iter().map(|name| (file_index, name.clone())));
+        }
+        let visible_set: HashSet<&(usize, String)> = visible.iter().collect();
+        self.visible_order.retain(|key| visible_set.contains(key));
+        let known: HashSet<(usize, String)> = self.visible_order.iter().cloned().collect();
+        for key in visible {
+            if !known.contains(&key) {
+                self.visible_order.push_back(key);
+            }
+        }
+
+        self.visible_limit_exceeded = None;
+        let max = self.user_settings.max_visible_signals;
+        if max == 0 || self.visible_order.len() <= max {
+            return false;
+        }
+        if !self.user_settings.evict_oldest_visible {
+            self.visible_limit_exceeded = Some(self.visible_order.len());
+            return false;
+        }
+        while self.visible_order.len() > max {
+            let Some((file_index, name)) = self.visible_order.pop_front() else {
+                break;
+            };
+            if let Some(sig) = self
+                .open_files
+                .get_mut(file_index)
+                .and_then(|f| f.signals.get_mut(&name))
+            {
+                sig.visible = false;
+            }
+        }
+        true
+    }
+
+    /// 有効なファイル (file_index 指定時はそのファイルのみ) の全ログの時刻を昇順で返す
+    fn event_times(&self, file_index: Option<usize>) -> Vec<f64> {
+        let mut times: Vec<f64> = self
+            .open_files
+            .iter()
+            .enumerate()
+            .filter(|(i, f)| f.enabled && file_index.is_none_or(|t| t == *i))
+            .flat_map(|(_, f)| f.logs.iter().map(|log| log.timestamp_num))
+            .collect();
+        times.sort_by(f64::total_cmp);
+        times
+    }
+
+    /// シグナル名のあいまい検索で候補を並べ、Enter かクリックで表示を切り替える
+    fn show_signal_palette(&mut self, ctx: &egui::Context) {
+        let palette = &mut self.signal_palette;
+        let (up, down, enter, escape) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::ArrowUp),
+                i.key_pressed(egui::Key::ArrowDown),
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        if escape {
+            palette.open = false;
+            return;
+        }
+        let mut toggle = None;
+        egui::Window::new("Toggle Signal")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut palette.query)
+                        .hint_text("Signal name")
+                        .desired_width(420.0),
+                );
+                response.request_focus();
+                if response.changed() {
+                    palette.selected = 0;
+                }
+
+                // (スコア, ファイル番号, グループ名, シグナル名, 表示中か)
+                let query = palette.query.trim();
+                let mut matches = Vec::new();
+                for (file_index, file_data) in self.open_files.iter().enumerate() {
+                    let mut group_by_signal: HashMap<&str, &str> = HashMap::new();
+                    for group in file_data.groups.values() {
+                        for s in &group.signals {
+                            group_by_signal
+                                .entry(s.as_str())
+                                .or_insert(group.name.as_str());
+                        }
+                    }
+                    for sig in file_data.signals.values() {
+                        if let Some(score) = fuzzy_score(&sig.name, query) {
+                            let group = group_by_signal.get(sig.name.as_str()).copied();
+                            matches.push((
+                                score,
+                                file_index,
+                                group.unwrap_or("").to_string(),
+                                sig.name.clone(),
+                                sig.visible,
+                            ));
+                        }
+                    }
+                }
+                matches.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.3.cmp(&b.3)));
+                matches.truncate(PALETTE_MAX_MATCHES);
+
+                if matches.is_empty() {
+                    ui.weak("No matching signals");
+                    return;
+                }
+                if down {
+                    palette.selected = (palette.selected + 1).min(matches.len() - 1);
+                }
+                if up {
+                    palette.selected = palette.selected.saturating_sub(1);
+                }
+                palette.selected = palette.selected.min(matches.len() - 1);
+                ui.separator();
+                for (row, (_, file_index, group, name, visible)) in matches.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let text = if *visible {
+                            egui::RichText::new(name).strong()
+                        } else {
+                            egui::RichText::new(name)
+                        };
+                        if ui.selectable_label(row == palette.selected, text).clicked() {
+                            toggle = Some((*file_index, name.clone()));
+                        }
+                        let file_name = &self.open_files[*file_index].file_name;
+                        let context = if group.is_empty() {
+                            file_name.clone()
+                        } else {
+                            format!("{} / {}", file_name, group)
+                        };
+                        ui.weak(context);
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.weak(if *visible { "shown" } else { "hidden" });
+                        });
+                    });
+                }
+                if enter {
+                    let (_, file_index, _, name, _) = &matches[palette.selected];
+                    toggle = Some((*file_index, name.clone()));
+                }
+            });
+        if let Some((file_index, name)) = toggle {
+            if let Some(sig) = self
+                .open_files
+                .get_mut(file_index)
+                .and_then(|f| f.signals.get_mut(&name))
+            {
+                sig.visible = !sig.visible;
+            }
+            self.signal_palette = SignalPaletteState::default();
+        }
+    }
+
+    /// 無通信区間の一覧を表示する (クリックでその区間へ移動)
+    fn show_gap_finder(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Longer than:");
+            ui.add(
+                egui::DragValue::new(&mut self.gap_finder.threshold)
+                    .range(0.001..=86_400.0)
+                    .speed(0.01)
+                    .suffix(" s"),
+            );
+            if ui.button("Find Gaps").clicked() {
+                let threshold = self.gap_finder.threshold;
+                self.gap_finder.gaps = self
+                    .event_times(None)
+                    .windows(2)
+                    .filter(|w| w[1] - w[0] > threshold)
+                    .map(|w| (w[0], w[1]))
+                    .collect();
+            }
+            if !self.gap_finder.gaps.is_empty() && ui.button("Clear").clicked() {
+                self.gap_finder.gaps.clear();
+            }
+        });
+        ui.label(format!("{} gap(s)", self.gap_finder.gaps.len()));
+        ui.separator();
+        let mut goto = None;
+        egui::ScrollArea::vertical()
+            .id_salt("gap_finder_scroll")
+            .max_height(300.0)
+            .show(ui, |ui| {
+                egui::Grid::new("gap_finder_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Start");
+                        ui.strong("End");
+                        ui.strong("Duration");
+                        ui.end_row();
+                        for &(start, end) in &self.gap_finder.gaps {
+                            if ui.link(format_iso(start)).clicked() {
+                                goto = Some((start + end) / 2.0);
+                            }
+                            ui.label(format_iso(end));
+                            ui.label(format!("{:.3} s", end - start));
+                            ui.end_row();
+                        }
+                    });
+            });
+        if goto.is_some() {
+            self.goto_time = goto;
+        }
+    }
+
+    /// 全ファイルを通した時刻範囲 (min, max) を返す
+    fn global_time_range(&self) -> (f64, f64) {
+        // ログが空のファイルは仮の範囲 (0.0〜10.0) なので除外する
+        let files = || {
+            self.open_files
+                .iter()
+                .filter(|f| f.enabled && !f.logs.is_empty())
+        };
+        let global_min_time = files().map(|f| f.min_time).fold(f64::INFINITY, f64::min);
+        let global_max_time = files()
+            .map(|f| f.max_time)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let global_min_time = if global_min_time == f64::INFINITY {
+            0.0
+        } else {
+            global_min_time
+        };
+        let global_max_time = if global_max_time == f64::NEG_INFINITY {
+            10.0
+        } else {
+            global_max_time
+        };
+        (global_min_time, global_max_time)
+    }
+
+    /// 可視シグナルのタイミングを Markdown のレポートにまとめる
+    fn build_timing_report(&self) -> String {
+        let (min_t, max_t) = self.global_time_range();
+        let mut out = String::new();
+        out.push_str("# Timing Report\n\n");
+        out.push_str(&format!(
+            "- Capture range: {} - {} ({:.6} s)\n",
+            format_iso(min_t),
+            format_iso(max_t),
+            max_t - min_t
+        ));
+        for file_data in &self.open_files {
+            out.push_str(&format!("\n## {}\n", file_data.file_name));
+            for group_key in file_data.ordered_group_keys() {
+                let group = &file_data.groups[&group_key];
+                let visible: Vec<&SignalData> = group
+                    .signals
+                    .iter()
+                    .filter_map(|s| file_data.signals.get(s))
+                    .filter(|sig| sig.visible)
+                    .collect();
+                if visible.is_empty() {
+                    continue;
+                }
+                out.push_str(&format!("\n### {}\n\n", group.name));
+                out.push_str("| Signal | Intervals | High time (s) | First edge | Last edge |\n");
+                out.push_str("|---|---:|---:|---|---|\n");
+                for sig in visible {
+                    out.push_str(&format!(
+                        "| {} | {} | {:.6} | {} | {} |\n",
+                        sig.name,
+                        sig.on_intervals.len(),
+                        sig.total_high,
+                        sig.first_edge
+                            .map(format_iso)
+                            .unwrap_or_else(|| "-".to_string()),
+                        sig.last_edge
+                            .map(format_iso)
+                            .unwrap_or_else(|| "-".to_string()),
+                    ));
+                }
+            }
+        }
+        out
+    }
+
+    fn export_report(&mut self) {
+        if self.open_files.is_empty() {
+            self.show_error_dialog("No file loaded.");
+            return;
+        }
+        if let Some(path) = FileDialog::new()
+            .add_filter("Markdown", &["md"])
+            .set_file_name("timing_report.md")
+            .save_file()
+        {
+            let report = self.build_timing_report();
+            if let Err(e) = fs::write(&path, report) {
+                self.show_error_dialog(&format!("Failed to write report: {}", e));
+            }
+        }
+    }
+
+    /// 選択中 (なければ先頭) のファイル名と、開いているファイル数をタイトルに出す
+    fn update_window_title(&mut self, ctx: &egui::Context) {
+        let file = self
+            .active_tab
+            .and_then(|i| self.open_files.get(i))
+            .or(self.open_files.first());
+        let title = match file {
+            None => APP_TITLE.to_string(),
+            Some(file) if self.open_files.len() == 1 => {
+                format!("{} — {}", APP_TITLE, file.file_name)
+            }
+            Some(file) => format!(
+                "{} — {} ({} files)",
+                APP_TITLE,
+                file.file_name,
+                self.open_files.len()
+            ),
+        };
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+    }
+
+    /// 画面全体のスクリーンショットを要求する (届いたら poll_plot_screenshot でコピーする)
+    fn request_plot_copy(&mut self, ctx: &egui::Context) {
+        if self.plot_rect.is_none() {
+            return;
+        }
+        self.copy_plot_pending = true;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::default()));
+    }
+
+    /// 届いたスクリーンショットからプロットの領域を切り出してクリップボードへコピーする
+    fn poll_plot_screenshot(&mut self, ctx: &egui::Context) {
+        if !self.copy_plot_pending {
+            return;
+        }
+        let screenshot = ctx.input(|i| {
+            i.events.iter().find_map(|e| match e {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        });
+        let (Some(image), Some(rect)) = (screenshot, self.plot_rect) else {
+            return;
+        };
+        self.copy_plot_pending = false;
+        ctx.copy_image(image.region(&rect, Some(ctx.pixels_per_point())));
+    }
+
+    fn save_svg(&mut self, svg: String, file_name: &str) {
+        if let Some(path) = FileDialog::new()
+            .add_filter("SVG", &["svg"])
+            .set_file_name(file_name)
+            .save_file()
+        {
+            if let Err(e) = fs::write(&path, svg) {
+                self.show_error_dialog(&format!("Failed to write SVG: {}", e));
+            }
+        }
+    }
+
+    /// 選択中のファイルを、現在の表示状態を default_visibility に含めて保存する
+    fn save_file_as(&mut self) {
+        let index = match self.active_tab {
+            Some(i) => i,
+            None if self.open_files.len() == 1 => 0,
+            None => {
+                self.show_error_dialog("Select a file tab to save.");
+                return;
+            }
+        };
+        let Some(file_data) = self.open_files.get(index) else {
+            self.show_error_dialog("No file loaded.");
+            return;
+        };
+        let Some(path) = FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name(&format!("{}.json", file_data.file_name))
+            .save_file()
+        else {
+            return;
+        };
+        let visibility = file_data.current_visibility();
+        let data = serde_json::json!({
+            "logs": &file_data.logs,
+            "default_visibility": visibility,
+        });
+        let result = serde_json::to_string_pretty(&data)
+            .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(&path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            self.show_error_dialog(&format!("Failed to save file: {}", e));
+        }
+    }
+
+    /// 可視シグナルのマージ後の区間を JSON に書き出す
+    fn export_intervals(&mut self) {
+        if self.open_files.is_empty() {
+            self.show_error_dialog("No file loaded.");
+            return;
+        }
+        let Some(path) = FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("intervals.json")
+            .save_file()
+        else {
+            return;
+        };
+        let mut exports = Vec::new();
+        for file_data in &self.open_files {
+            let mut names: Vec<&String> = file_data.signals.keys().collect();
+            names.sort();
+            for name in names {
+                let sig = &file_data.signals[name];
+                if !sig.visible {
+                    continue;
+                }
+                exports.push(IntervalExport {
+                    file: file_data.file_name.clone(),
+                    signal: sig.name.clone(),
+                    intervals: sig
+                        .on_intervals
+                        .iter()
+                        .map(|iv| [format_iso(iv.start), format_iso(iv.end)])
+                        .collect(),
+                });
+            }
+        }
+        let result = serde_json::to_string_pretty(&exports)
+            .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(&path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            self.show_error_dialog(&format!("Failed to export intervals: {}", e));
+        }
+    }
+
+    /// 1ファイル分のグループ/シグナルツリーを描画する (エラーがあればメッセージを返す)
+    fn show_file_tree(
+        ui: &mut egui::Ui,
+        file_index: usize,
+        file_data: &mut FileData,
+        mut focus: Option<&mut TreeFocus>,
+        selection: &mut SignalSelection,
+        tree: FileTreeContext,
+    ) -> Option<String> {
+        let FileTreeContext {
+            pinned,
+            solo_group,
+            file_names,
+            file_signals,
+        } = tree;
+        let mut error = None;
+        // 表示名の編集中はヘッダの代わりに入力欄を出す (Enter で確定、Esc で取消)
+        if let Some(buffer) = &mut file_data.rename_buffer {
+            let response = ui.text_edit_singleline(buffer);
+            if response.lost_focus() {
+                let name = buffer.trim().to_string();
+                if !name.is_empty() && !ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    file_data.file_name = name;
+                }
+                file_data.rename_buffer = None;
+            } else {
+                response.request_focus();
+            }
+            return error;
+        }
+        // スクロール待ちのシグナルがあれば、それを含むヘッダを開いておく
+        let reveal = focus
+            .as_ref()
+            .filter(|f| f.scroll_pending)
+            .map(|f| f.signal.clone());
+        // 無効にしたファイルは見出しを薄く表示する
+        let header_text = if file_data.enabled {
+            egui::RichText::new(&file_data.file_name)
+        } else {
+            egui::RichText::new(&file_data.file_name)
+                .weak()
+                .strikethrough()
+        };
+        let tree = egui::CollapsingHeader::new(header_text)
+            .id_salt(("file_tree", file_index))
+            .default_open(true)
+            .open(reveal.as_ref().map(|_| true))
+            .show(ui, |ui| {
+                let file_all_visible = file_data.signals.values().all(|sig| sig.visible);
+                let mut file_toggle = file_all_visible;
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut file_data.enabled, "Enabled")
+                        .on_hover_text(
+                            "Uncheck to hide this file from the plot without closing it",
+                        );
+                    if ui.checkbox(&mut file_toggle, "Toggle All").changed() {
+                        for sig in file_data.signals.values_mut() {
+                            sig.visible = file_toggle;
+                        }
+                    }
+                    if ui
+                        .button("Open Folder")
+                        .on_hover_text(file_data.file_path.display().to_string())
+                        .clicked()
+                    {
+                        if let Err(e) = open_containing_folder(&file_data.file_path) {
+                            error = Some(format!("Failed to open folder: {}", e));
+                        }
+                    }
+                });
+                // 手動の選択がファイルの default_visibility と異なる場合に知らせる
+                let diff = file_data.visibility_diff();
+                if !diff.is_empty() && diff != file_data.accepted_visibility_diff {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(
+                            Color32::YELLOW,
+                            format!("{} differ from file defaults", diff.len()),
+                        )
+                        .on_hover_text(diff.join("\n"));
+                        if ui.button("Revert to file defaults").clicked() {
+                            file_data.revert_visibility();
+                            file_data.accepted_visibility_diff.clear();
+                        }
+                        if ui.button("Keep current").clicked() {
+                            file_data.accepted_visibility_diff = diff.clone();
+                        }
+                    });
+                }
+                let mut group_move = None;
+                for group_key in file_data.ordered_group_keys() {
+                    if let Some(group) = file_data.groups.get_mut(&group_key) {
+                        let group_all_visible =
+                            group.signals.iter().all(|s| file_data.signals[s].visible);
+                        let reveal_group =
+                            reveal.as_ref().is_some_and(|r| group.signals.contains(r));
+                        let header = egui::CollapsingHeader::new(&group.name)
+                            .default_open(false)
+                            .open(reveal_group.then_some(true))
+                            .show(ui, |ui| {
+                                let mut group_toggle = group_all_visible;
+                                if ui.checkbox(&mut group_toggle, "Toggle All").changed() {
+                                    for s in &group.signals {
+                                        if let Some(sig) = file_data.signals.get_mut(s) {
+                                            sig.visible = group_toggle;
+                                        }
+                                    }
+                                }
+                                ui.horizontal(|ui| {
+                                    let mut use_color = group.color.is_some();
+                                    if ui.checkbox(&mut use_color, "Group color").changed() {
+                                        group.color = if use_color {
+                                            Some(Color32::LIGHT_BLUE)
+                                        } else {
+                                            None
+                                        };
+                                    }
+                                    if let Some(color) = &mut group.color {
+                                        ui.color_edit_button_srgba(color);
+                                    }
+                                });
+                                let rows: Vec<(usize, String)> = group
+                                    .signals
+                                    .iter()
+                                    .map(|s| (file_index, s.clone()))
+                                    .collect();
+                                let mut signal_move = None;
+                                ui.indent("group_signals", |ui| {
+                                    for s in &group.signals {
+                                        if let Some(sig) = file_data.signals.get_mut(s) {
+                                            let mut check = sig.visible;
+                                            let response = signal_checkbox(
+                                                ui,
+                                                &mut check,
+                                                &sig.name,
+                                                sig,
+                                                (file_data.min_time, file_data.max_time),
+                                            );
+                                            let key = (file_index, sig.name.clone());
+                                            if !selection.handle_click(
+                                                ui,
+                                                &response,
+                                                key.clone(),
+                                                &rows,
+                                            ) && response.changed()
+                                            {
+                                                sig.visible = check;
+                                            }
+                                            selection.paint_row(ui, &response, &key);
+                                            // 行をドラッグして同じグループ内で並べ替える
+                                            let drag = response.interact(egui::Sense::drag());
+                                            drag.dnd_set_drag_payload(SignalDrag {
+                                                file_index,
+                                                group: group_key.clone(),
+                                                signal: s.clone(),
+                                            });
+                                            if let Some(payload) =
+                                                drag.dnd_release_payload::<SignalDrag>()
+                                            {
+                                                if payload.file_index == file_index
+                                                    && payload.group == group_key
+                                                {
+                                                    signal_move =
+                                                        Some((payload.signal.clone(), s.clone()));
+                                                }
+                                            } else if drag
+                                                .dnd_hover_payload::<SignalDrag>()
+                                                .is_some_and(|p| {
+                                                    p.file_index == file_index
+                                                        && p.group == group_key
+                                                })
+                                            {
+                                                paint_drop_marker(ui, &response);
+                                            }
+                                            if let Some(f) = focus.as_deref_mut() {
+                                                if f.signal == sig.name {
+                                                    highlight_tree_row(ui, &response, f);
+                                                }
+                                            }
+                                            response.context_menu(|ui| {
+                                                let pin_key = (file_index, sig.name.clone());
+                                                let is_pinned = pinned.contains(&pin_key);
+                                                let pin_label =
+                                                    if is_pinned { "Unpin" } else { "Pin to top" };
+                                                if ui.button(pin_label).clicked() {
+                                                    if is_pinned {
+                                                        pinned.remove(&pin_key);
+                                                    } else {
+                                                        pinned.insert(pin_key);
+                                                    }
+                                                    ui.close_menu();
+                                                }
+                                                ui.horizontal(|ui| {
+                                                    ui.label("Line width:");
+                                                    ui.add(
+                                                        egui::DragValue::new(&mut sig.line_width)
+                                                            .range(0.5..=8.0)
+                                                            .speed(0.1),
+                                                    );
+                                                });
+                                                if !sig.analog_samples.is_empty() {
+                                                    ui.horizontal(|ui| {
+                                                        ui.label("Gain:");
+                                                        ui.add(
+                                                            egui::DragValue::new(
+                                                                &mut sig.analog_gain,
+                                                            )
+                                                            .range(0.1..=10.0)
+                                                            .speed(0.01),
+                                                        );
+                                                        ui.label("Offset:");
+                                                        ui.add(
+                                                            egui::DragValue::new(
+                                                                &mut sig.analog_offset,
+                                                            )
+                                                            .range(-1.0..=1.0)
+                                                            .speed(0.01),
+                                                        );
+                                                    });
+                                                }
+                                                ui.separator();
+                                                let transform =
+                                                    if ui.button("Duplicate as inverted").clicked()
+                                                    {
+                                                        Some(DerivedTransform::Invert)
+                                                    } else if ui
+                                                        .button("Duplicate with time offset")
+                                                        .clicked()
+                                                    {
+                                                        Some(DerivedTransform::Offset(0.0))
+                                                    } else {
+                                                        None
+                                                    };
+                                                // 他のファイルの同名シグナルとの差分レーン
+                                                let mut diff_with = None;
+                                                let others: Vec<(usize, &String)> = file_names
+                                                    .iter()
+                                                    .enumerate()
+                                                    .filter(|(other, _)| {
+                                                        *other != file_index
+                                                            && file_signals.get(*other).is_some_and(
+                                                                |s| s.contains(&sig.name),
+                                                            )
+                                                    })
+                                                    .collect();
+                                                if !others.is_empty() {
+                                                    ui.menu_button("Diff with", |ui| {
+                                                        for (other, name) in others {
+                                                            if ui.button(name).clicked() {
+                                                                diff_with = Some(other);
+                                                            }
+                                                        }
+                                                    });
+                                                }
+                                                let transform = transform
+                                                    .or(diff_with.map(DerivedTransform::Diff));
+                                                if let Some(transform) = transform {
+                                                    file_data.derived.push(DerivedSignal {
+                                                        source: sig.name.clone(),
+                                                        transform,
+                                                        visible: true,
+                                                    });
+                                                    ui.close_menu();
+                                                }
+                                            });
+                                        }
+                                    }
+                                });
+                                if let Some((from, to)) = signal_move {
+                                    move_item(&mut group.signals, &from, &to);
+                                }
+                            });
+                        // グループの見出しをドラッグしてグループの順序を入れ替える
+                        let drag = header.header_response.interact(egui::Sense::drag());
+                        drag.dnd_set_drag_payload(GroupDrag {
+                            file_index,
+                            group: group_key.clone(),
+                        });
+                        if let Some(payload) = drag.dnd_release_payload::<GroupDrag>() {
+                            if payload.file_index == file_index {
+                                group_move = Some((payload.group.clone(), group_key.clone()));
+                            }
+                        } else if drag
+                            .dnd_hover_payload::<GroupDrag>()
+                            .is_some_and(|p| p.file_index == file_index)
+                        {
+                            paint_drop_marker(ui, &drag);
+                        }
+                        // 見出しにカーソルを重ねると、開かずにメンバーと表示数を確認できる
+                        let drag = drag.on_hover_ui(|ui| {
+                            let visible_count = group
+                                .signals
+                                .iter()
+                                .filter(|s| {
+                                    file_data.signals.get(*s).is_some_and(|sig| sig.visible)
+                                })
+                                .count();
+                            ui.label(format!(
+                                "{} / {} visible",
+                                visible_count,
+                                group.signals.len()
+                            ));
+                            ui.separator();
+                            for s in group.signals.iter().take(GROUP_TOOLTIP_MAX_SIGNALS) {
+                                let visible =
+                                    file_data.signals.get(s).is_some_and(|sig| sig.visible);
+                                if visible {
+                                    ui.label(s);
+                                } else {
+                                    ui.weak(s);
+                                }
+                            }
+                            if group.signals.len() > GROUP_TOOLTIP_MAX_SIGNALS {
+                                ui.weak(format!(
+                                    "... and {} more",
+                                    group.signals.len() - GROUP_TOOLTIP_MAX_SIGNALS
+                                ));
+                            }
+                        });
+                        let solo_key = (file_index, group_key.clone());
+                        let is_solo = solo_group.as_ref() == Some(&solo_key);
+                        drag.context_menu(|ui| {
+                            let solo_label = if is_solo { "Clear solo" } else { "Solo group" };
+                            if ui.button(solo_label).clicked() {
+                                *solo_group = if is_solo { None } else { Some(solo_key) };
+                                ui.close_menu();
+                            }
+                        });
+                        ui.separator();
+                    }
+                }
+                if let Some((from, to)) = group_move {
+                    let mut keys = file_data.ordered_group_keys();
+                    move_item(&mut keys, &from, &to);
+                    file_data.group_order = keys;
+                }
+                if !file_data.derived.is_empty() {
+                    egui::CollapsingHeader::new("Derived")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            let mut remove_index = None;
+                            for (i, derived) in file_data.derived.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let label = derived.label(file_names);
+                                    ui.checkbox(&mut derived.visible, label);
+                                    if let DerivedTransform::Offset(dt) = &mut derived.transform {
+                                        ui.add(egui::DragValue::new(dt).speed(0.001).suffix(" s"));
+                                    }
+                                    if ui.button("-").clicked() {
+                                        remove_index = Some(i);
+                                    }
+                                });
+                            }
+                            if let Some(i) = remove_index {
+                                file_data.derived.remove(i);
+                            }
+                        });
+                }
+            });
+        if tree
+            .header_response
+            .on_hover_text("Double-click to rename")
+            .double_clicked()
+        {
+            file_data.rename_buffer = Some(file_data.file_name.clone());
+        }
+        error
+    }
+
+    /// 全ファイルの同名グループを1つにまとめたツリーを描画する
+    fn show_merged_group_tree(&mut self, ui: &mut egui::Ui) {
+        // グループ名 → (ファイル番号, シグナル名) の一覧
+        let mut merged: BTreeMap<String, Vec<(usize, String)>> = BTreeMap::new();
+        for (file_index, file_data) in self.open_files.iter().enumerate() {
+            for group in file_data.groups.values() {
+                let members = merged.entry(group.name.clone()).or_default();
+                for s in &group.signals {
+                    members.push((file_index, s.clone()));
+                }
+            }
+        }
+        for (group_name, members) in merged {
+            let group_all_visible = members.iter().all(|(file_index, s)| {
+                self.open_files[*file_index]
+                    .signals
+                    .get(s)
+                    .is_some_and(|sig| sig.visible)
+            });
+            let reveal_group = self.tree_focus.as_ref().is_some_and(|f| {
+                f.scroll_pending && members.contains(&(f.file_index, f.signal.clone()))
+            });
+            egui::CollapsingHeader::new(&group_name)
+                .id_salt(("merged_group", &group_name))
+                .default_open(false)
+                .open(reveal_group.then_some(true))
+                .show(ui, |ui| {
+                    let mut group_toggle = group_all_visible;
+                    if ui.checkbox(&mut group_toggle, "Toggle All").changed() {
+                        for (file_index, s) in &members {
+                            if let Some(sig) = self.open_files[*file_index].signals.get_mut(s) {
+                                sig.visible = group_toggle;
+                            }
+                        }
+                    }
+                    ui.indent("merged_group_signals", |ui| {
+                        for (file_index, s) in &members {
+                            let file_data = &mut self.open_files[*file_index];
+                            if let Some(sig) = file_data.signals.get_mut(s) {
+                                let mut check = sig.visible;
+                                let label = format!("{} ({})", sig.name, file_data.file_name);
+                                let span = (file_data.min_time, file_data.max_time);
+                                let response = signal_checkbox(ui, &mut check, &label, sig, span);
+                                let key = (*file_index, s.clone());
+                                if !self.selection.handle_click(
+                                    ui,
+                                    &response,
+                                    key.clone(),
+                                    &members,
+                                ) && response.changed()
+                                {
+                                    sig.visible = check;
+                                }
+                                self.selection.paint_row(ui, &response, &key);
+                                if let Some(f) = self.tree_focus.as_mut() {
+                                    if f.file_index == *file_index && f.signal == *s {
+                                        highlight_tree_row(ui, &response, f);
+                                    }
+                                }
+                            }
+                        }
+                    });
+                });
+            ui.separator();
+        }
+    }
+
+    /// ピン留めしたシグナルを左ペインの先頭にまとめて表示する
+    fn show_pinned_signals(&mut self, ui: &mut egui::Ui) {
+        if self.pinned.is_empty() {
+            return;
+        }
+        let mut unpin = None;
+        egui::CollapsingHeader::new("Pinned")
+            .default_open(true)
+            .show(ui, |ui| {
+                for key in &self.pinned {
+                    let (file_index, name) = key;
+                    let Some(file_data) = self.open_files.get_mut(*file_index) else {
+                        continue;
+                    };
+                    let label = format!("{} ({})", name, file_data.file_name);
+                    let Some(sig) = file_data.signals.get_mut(name) else {
+                        continue;
+                    };
+                    ui.horizontal(|ui| {
+                        let mut check = sig.visible;
+                        let span = (file_data.min_time, file_data.max_time);
+                        if signal_checkbox(ui, &mut check, &label, sig, span).changed() {
+                            sig.visible = check;
+                        }
+                        if ui.small_button("Unpin").clicked() {
+                            unpin = Some(key.clone());
+                        }
+                    });
+                }
+            });
+        if let Some(key) = unpin {
+            self.pinned.remove(&key);
+        }
+        ui.separator();
+    }
+
+    /// 複数選択したシグナルへの一括操作バーを描画する
+    fn show_selection_bar(&mut self, ui: &mut egui::Ui) {
+        if self.selection.selected.is_empty() {
+            ui.weak("Ctrl/Shift+click to select multiple signals");
+            ui.separator();
+            return;
+        }
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!("{} selected", self.selection.selected.len()));
+            let visible = if ui.button("Show selected").clicked() {
+                Some(true)
+            } else if ui.button("Hide selected").clicked() {
+                Some(false)
+            } else {
+                None
+            };
+            ui.color_edit_button_srgba(&mut self.selection.color);
+            let set_color = ui.button("Set color").clicked();
+            let reset_color = ui.button("Reset color").clicked();
+            for (file_index, name) in &self.selection.selected {
+                let Some(file_data) = self.open_files.get_mut(*file_index) else {
+                    continue;
+                };
+                if let (Some(visible), Some(sig)) = (visible, file_data.signals.get_mut(name)) {
+                    sig.visible = visible;
+                }
+                if set_color {
+                    file_data
+                        .signal_colors
+                        .insert(name.clone(), self.selection.color);
+                } else if reset_color {
+                    file_data.signal_colors.remove(name);
+                }
+            }
+            if ui.button("Clear selection").clicked() {
+                self.selection.selected.clear();
+                self.selection.anchor = None;
+            }
+        });
+        ui.separator();
+    }
+
+    /// 全イベント (または選択したシグナル) の時間ごとの件数を棒グラフで描画する
+    fn show_histogram(&mut self, ui: &mut egui::Ui) {
+        if self.open_files.is_empty() {
+            ui.label("No file loaded.");
+            return;
+        }
+        let state = &mut self.histogram;
+        if state
+            .source
+            .as_ref()
+            .is_some_and(|(i, _)| *i >= self.open_files.len())
+        {
+            state.source = None;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Source:");
+            let selected_text = match &state.source {
+                Some((i, name)) => format!("{} / {}", self.open_files[*i].file_name, name),
+                None => "All events".to_string(),
+            };
+            egui::ComboBox::from_id_salt("histogram_source")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut state.source, None, "All events");
+                    for (i, file_data) in self.open_files.iter().enumerate() {
+                        let mut names: Vec<&String> = file_data.signals.keys().collect();
+                        names.sort();
+                        for name in names {
+                            ui.selectable_value(
+                                &mut state.source,
+                                Some((i, name.clone())),
+                                format!("{} / {}", file_data.file_name, name),
+                            );
+                        }
+                    }
+                });
+            ui.checkbox(&mut state.durations, "Interval durations");
+            if state.durations {
+                ui.label("Bins:");
+                ui.add(egui::DragValue::new(&mut state.duration_bins).range(1..=1000));
+            } else {
+                ui.label("Bucket width:");
+                ui.add(
+                    egui::DragValue::new(&mut state.bucket_width)
+                        .range(0.001..=3600.0)
+                        .speed(0.01)
+                        .suffix(" s"),
+                );
+            }
+        });
+        if self.histogram.durations {
+            self.show_duration_histogram(ui);
+            return;
+        }
+
+        let (min_t, max_t) = self.global_time_range();
+        let bucket_width = self.histogram.bucket_width;
+        let bucket_count = ((max_t - min_t) / bucket_width).ceil().max(1.0) as usize;
+        if bucket_count > HISTOGRAM_MAX_BUCKETS {
+            ui.colored_label(
+                Color32::YELLOW,
+                format!(
+                    "Too many buckets ({}). Increase the bucket width.",
+                    bucket_count
+                ),
+            );
+            return;
+        }
+        let mut counts = vec![0usize; bucket_count];
+        for (file_index, file_data) in self.open_files.iter().enumerate() {
+            for log in &file_data.logs {
+                let matches = match &self.histogram.source {
+                    Some((i, name)) => *i == file_index && log.name == *name,
+                    None => true,
+                };
+                if matches {
+                    let bucket = ((log.timestamp_num - min_t) / bucket_width) as usize;
+                    counts[bucket.min(bucket_count - 1)] += 1;
+                }
+            }
+        }
+        let bars: Vec<egui_plot::Bar> = counts
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| {
+                egui_plot::Bar::new(min_t + (i as f64 + 0.5) * bucket_width, count as f64)
+                    .width(bucket_width)
+            })
+            .collect();
+
+        let time_axis_decimals = self.user_settings.time_axis_decimals;
+        let display_timezone = self.user_settings.display_timezone;
+        let relative_time = self.relative_time;
+        egui_plot::Plot::new("event_histogram_plot")
+            .height(250.0)
+            .include_y(0.0)
+            .x_axis_formatter(
+                move |grid_mark: egui_plot::GridMark, _range: &RangeInclusive<f64>| {
+                    if relative_time {
+                        format_relative_time(grid_mark.value - min_t, time_axis_decimals)
+                    } else {
+                        format_time_of_day(grid_mark.value, time_axis_decimals, display_timezone)
+                    }
+                },
+            )
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(
+                    egui_plot::BarChart::new(bars)
+                        .color(Color32::LIGHT_BLUE)
+                        .name("Events"),
+                );
+            });
+    }
+
+    /// 選択したシグナルの High 区間の長さの分布を棒グラフで描画する
+    fn show_duration_histogram(&mut self, ui: &mut egui::Ui) {
+        let Some((file_index, name)) = &self.histogram.source else {
+            ui.label("Select a signal to see its interval durations.");
+            return;
+        };
+        let Some(sig) = self.open_files[*file_index].signals.get(name) else {
+            return;
+        };
+        let title = format!("{} / {}", self.open_files[*file_index].file_name, name);
+        let durations: Vec<f64> = sig
+            .on_intervals
+            .iter()
+            .map(|iv| iv.end - iv.start)
+            .collect();
+        if durations.is_empty() {
+            ui.label("This signal has no intervals.");
+            return;
+        }
+        let max_d = durations.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let (min_d, bin_width, counts) = bin_durations(&durations, self.histogram.duration_bins);
+        let export = ui
+            .horizontal(|ui| {
+                ui.label(format!(
+                    "{} intervals, {:.3} ms - {:.3} ms",
+                    durations.len(),
+                    min_d * 1000.0,
+                    max_d * 1000.0
+                ));
+                ui.button("Export SVG...").clicked()
+            })
+            .inner;
+        // 横軸はミリ秒で表示する
+        let bars: Vec<egui_plot::Bar> = counts
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| {
+                egui_plot::Bar::new(
+                    (min_d + (i as f64 + 0.5) * bin_width) * 1000.0,
+                    count as f64,
+                )
+                .width(bin_width * 1000.0)
+            })
+            .collect();
+        egui_plot::Plot::new("duration_histogram_plot")
+            .height(250.0)
+            .include_y(0.0)
+            .x_axis_formatter(
+                |grid_mark: egui_plot::GridMark, _range: &RangeInclusive<f64>| {
+                    format!("{} ms", grid_mark.value)
+                },
+            )
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(
+                    egui_plot::BarChart::new(bars)
+                        .color(Color32::LIGHT_GREEN)
+                        .name("Intervals"),
+                );
+            });
+        if export {
+            let [bg_r, bg_g, bg_b] = self.user_settings.plot_background_color;
+            let [grid_r, grid_g, grid_b] = self.user_settings.plot_grid_color;
+            let svg = histogram_svg(
+                &title,
+                min_d * 1000.0,
+                bin_width * 1000.0,
+                &counts,
+                Color32::LIGHT_GREEN,
+                Color32::from_rgb(bg_r, bg_g, bg_b),
+                Color32::from_rgb(grid_r, grid_g, grid_b),
+            );
+            self.save_svg(svg, "duration_histogram.svg");
+        }
+    }
+
+    /// カーソル位置で各可視シグナルが High/Low か (数値は補間した値) を一覧表示する
+    fn show_inspector(&self, ui: &mut egui::Ui) {
+        ui.heading("Inspector");
+        let Some(t) = self.cursor_time else {
+            ui.label("Click the plot to place the cursor.");
+            return;
+        };
+        let (min_t, _) = self.global_time_range();
+        let time_label = if self.relative_time {
+            format_relative_time(t - min_t, self.user_settings.time_axis_decimals)
+        } else {
+            format_time_of_day(
+                t,
+                self.user_settings.time_axis_decimals,
+                self.user_settings.display_timezone,
+            )
+        };
+        ui.label(format!("Cursor: {}", time_label));
+        ui.separator();
+        let mut rows: Vec<(String, String, bool)> = Vec::new();
+        for file_data in self.open_files.iter().filter(|f| f.enabled) {
+            for sig in file_data.signals.values().filter(|s| s.visible) {
+                let unit = find_signal_unit(&self.user_settings.signal_units, &sig.name);
+                let (state, high) = signal_state_at(sig, t, unit);
+                rows.push((
+                    format!("{} / {}", file_data.file_name, sig.name),
+                    state,
+                    high,
+                ));
+            }
+        }
+        rows.sort();
+        egui::ScrollArea::vertical()
+            .id_salt("inspector_scroll")
+            .show(ui, |ui| {
+                egui::Grid::new("inspector_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (name, state, high) in &rows {
+                            ui.label(name);
+                            if *high {
+                                ui.colored_label(Color32::LIGHT_GREEN, state);
+                            } else {
+                                ui.label(state);
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    /// ログエントリ一覧テーブルを描画する
+    fn show_log_table(&mut self, ui: &mut egui::Ui) {
+        if self.open_files.is_empty() {
+            ui.label("No file loaded.");
+            return;
+        }
+        let table = &mut self.log_table;
+        if table.file_index >= self.open_files.len() {
+            table.file_index = 0;
+            table.selected = None;
+        }
+        ui.horizontal(|ui| {
+            ui.label("File:");
+            egui::ComboBox::from_id_salt("log_table_file")
+                .selected_text(&self.open_files[table.file_index].file_name)
+                .show_ui(ui, |ui| {
+                    for (i, file_data) in self.open_files.iter().enumerate() {
+                        if ui
+                            .selectable_value(&mut table.file_index, i, &file_data.file_name)
+                            .changed()
+                        {
+                            table.selected = None;
+                        }
+                    }
+                });
+            ui.separator();
+            ui.label("Filter:");
+            ui.add(
+                egui::TextEdit::singleline(&mut table.filter)
+                    .hint_text("name, kind, value or comment")
+                    .desired_width(200.0),
+            );
+            if !table.filter.is_empty() && ui.button("Clear").clicked() {
+                table.filter.clear();
+            }
+            ui.checkbox(&mut table.follow_plot, "Plot range only")
+                .on_hover_text("Show only entries inside the plot's visible time range");
+            let total = self.open_files[table.file_index].logs.len();
+            if table.filter.is_empty() && !table.follow_plot {
+                ui.label(format!("{} entries", total));
+            } else {
+                ui.label(format!("{} / {} entries", table.order.len(), total));
+            }
+        });
+        ui.separator();
+
+        let logs = &self.open_files[table.file_index].logs;
+
+        // ヘッダ：クリックでソート列/昇順降順を切り替える
+        ui.horizontal(|ui| {
+            for column in LogColumn::ALL {
+                let mut title = column.title().to_string();
+                if table.sort_column == column {
+                    title.push_str(if table.ascending { " ▲" } else { " ▼" });
+                }
+                let header = egui::Button::new(egui::RichText::new(title).strong()).frame(false);
+                if ui.add_sized([column.width(), 18.0], header).clicked() {
+                    if table.sort_column == column {
+                        table.ascending = !table.ascending;
+                    } else {
+                        table.sort_column = column;
+                        table.ascending = true;
+                    }
+                }
+            }
         });
+        table.update_order(logs, self.plot_time_range);
+
+        // 表示範囲の行だけ描画する
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        let mut clicked_row = None;
+        egui::ScrollArea::both()
+            .id_salt("log_table_scroll")
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, table.order.len(), |ui, row_range| {
+                for row in row_range {
+                    let idx = table.order[row];
+                    let log = &logs[idx];
+                    let fill = if table.selected == Some(idx) {
+                        ui.visuals().selection.bg_fill
+                    } else {
+                        Color32::TRANSPARENT
+                    };
+                    let response = egui::Frame::new()
+                        .fill(fill)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                for column in LogColumn::ALL {
+                                    ui.add_sized(
+                                        [column.width(), row_height],
+                                        egui::Label::new(column.cell_text(log)).truncate(),
+                                    );
+                                }
+                            });
+                        })
+                        .response;
+                    let response = ui.interact(
+                        response.rect,
+                        ui.id().with(("log_row", idx)),
+                        egui::Sense::click(),
+                    );
+                    if response.clicked() {
+                        clicked_row = Some(idx);
+                    }
+                }
+            });
+        if let Some(idx) = clicked_row {
+            table.selected = Some(idx);
+            self.highlight_time = Some(logs[idx].timestamp_num);
+        }
     }
 
-    /// デジタル波形を生成する
-    fn build_digital_wave(on_intervals: &[Interval], min_t: f64, max_t: f64, offset: f64) -> Line {
+    /// デジタル波形の折れ線の点を生成する (プロットと SVG 書き出しで共通)
+    fn digital_wave_points(
+        on_intervals: &[Interval],
+        min_t: f64,
+        max_t: f64,
+        offset: f64,
+    ) -> Vec<[f64; 2]> {
         let mut points = Vec::new();
         let mut current_x = min_t;
         points.push([current_x, offset]);
@@ -407,28 +4651,369 @@ impl MyApp {
         if current_x < max_t {
             points.push([max_t, offset]);
         }
-        Line::new(PlotPoints::from(points))
+        points
+    }
+
+    /// 数値サンプルをレーン内 (offset+0.1 〜 offset+0.9) に正規化した折れ線として描画し、
+    /// 最小値/最大値の位置に目安線と値ラベルを添える
+    fn build_analog_track(
+        plot_ui: &mut PlotUi,
+        sig: &SignalData,
+        offset: f64,
+        color: Color32,
+        label: &str,
+        settings: &UserSettings,
+        warp: &TimeWarp,
+    ) {
+        // シグナル名で設定したしきい値と単位
+        let threshold = settings
+            .analog_thresholds
+            .iter()
+            .find(|th| th.signal == sig.name)
+            .map(|th| th.value);
+        let unit = find_signal_unit(&settings.signal_units, &sig.name);
+        let format_value = |v: f64| match unit {
+            Some(unit) => unit.format(v),
+            None => format!("{}", v),
+        };
+        let range = sig.value_max - sig.value_min;
+        let to_y = |v: f64| analog_lane_y(sig, offset, v);
+        let points: Vec<[f64; 2]> = sig
+            .analog_samples
+            .iter()
+            .map(|(t, v)| [warp.to_display(*t), to_y(*v)])
+            .collect();
+        plot_ui.line(
+            Line::new(PlotPoints::from(points))
+                .color(color)
+                .width(sig.line_width)
+                .name(label),
+        );
+
+        // 最小値/最大値の目安線 (サンプルのある範囲だけ)
+        let (Some(&(t_first, _)), Some(&(t_last, _))) =
+            (sig.analog_samples.first(), sig.analog_samples.last())
+        else {
+            return;
+        };
+        let (t_first, t_last) = (warp.to_display(t_first), warp.to_display(t_last));
+
+        // しきい値を超えた部分を警告色で重ね描きする
+        if let Some(threshold) = threshold {
+            let warn = Color32::from_rgb(255, 80, 80);
+            for run in runs_above(&sig.analog_samples, threshold) {
+                let points: Vec<[f64; 2]> = run
+                    .iter()
+                    .map(|(t, v)| [warp.to_display(*t), to_y(*v)])
+                    .collect();
+                plot_ui.line(
+                    Line::new(PlotPoints::from(points))
+                        .color(warn)
+                        .width(2.5)
+                        .name(label),
+                );
+            }
+            // しきい値の線は値の範囲内にあるときだけ引く (範囲外だとレーン端に張り付くため)
+            if (sig.value_min..=sig.value_max).contains(&threshold) {
+                let y = to_y(threshold);
+                plot_ui.line(
+                    Line::new(PlotPoints::from(vec![[t_first, y], [t_last, y]]))
+                        .color(warn.gamma_multiply(0.6))
+                        .style(egui_plot::LineStyle::dashed_loose())
+                        .width(1.0)
+                        .name(label),
+                );
+                plot_ui.text(
+                    Text::new(
+                        PlotPoint::new(t_last, y),
+                        egui::RichText::new(format_value(threshold))
+                            .small()
+                            .color(warn),
+                    )
+                    .anchor(egui::Align2::RIGHT_BOTTOM)
+                    .name(label),
+                );
+            }
+        }
+        let extremes: &[(f64, egui::Align2)] = if range > 0.0 {
+            &[
+                (sig.value_min, egui::Align2::LEFT_TOP),
+                (sig.value_max, egui::Align2::LEFT_BOTTOM),
+            ]
+        } else {
+            &[(sig.value_min, egui::Align2::LEFT_BOTTOM)]
+        };
+        for &(value, anchor) in extremes {
+            let y = to_y(value);
+            plot_ui.line(
+                Line::new(PlotPoints::from(vec![[t_first, y], [t_last, y]]))
+                    .color(color.gamma_multiply(0.3))
+                    .style(egui_plot::LineStyle::dashed_dense())
+                    .width(1.0)
+                    .name(label),
+            );
+            plot_ui.text(
+                Text::new(
+                    PlotPoint::new(t_first, y),
+                    egui::RichText::new(format_value(value))
+                        .small()
+                        .color(color.gamma_multiply(0.7)),
+                )
+                .anchor(anchor)
+                .name(label),
+            );
+        }
+    }
+
+    /// 文字列値の状態遷移を、値ごとのラベル付きボックスとして描画する
+    fn build_state_track(
+        plot_ui: &mut PlotUi,
+        samples: &[(f64, String)],
+        max_t: f64,
+        offset: f64,
+        color: Color32,
+        label: &str,
+        warp: &TimeWarp,
+    ) {
+        for (i, (start, value)) in samples.iter().enumerate() {
+            let end = samples.get(i + 1).map(|(t, _)| *t).unwrap_or(max_t);
+            if end <= *start {
+                continue;
+            }
+            let (start, end) = (warp.to_display(*start), warp.to_display(end));
+            let low = offset + 0.1;
+            let high = offset + 0.9;
+            let rect = vec![[start, low], [end, low], [end, high], [start, high]];
+            plot_ui.polygon(
+                Polygon::new(PlotPoints::from(rect))
+                    .fill_color(color.gamma_multiply(0.25))
+                    .stroke(egui::Stroke::new(1.0, color))
+                    .name(label),
+            );
+            plot_ui.text(
+                Text::new(
+                    PlotPoint::new((start + end) / 2.0, offset + 0.5),
+                    egui::RichText::new(value).color(Color32::WHITE),
+                )
+                .name(label),
+            );
+        }
     }
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         ctx.set_visuals(egui::Visuals::dark());
+        self.poll_background_task(ctx);
+        self.poll_watched_files(ctx);
+        self.poll_plot_screenshot(ctx);
+        self.update_window_title(ctx);
+
+        // 起動引数のファイルを1つずつ読み込む (変換中・スクリプト選択中・ダイアログ表示中は待つ)
+        if !self.busy
+            && self.pending_import_file.is_none()
+            && self.conversion_result.is_none()
+            && self.error_dialog_message.is_none()
+        {
+            if let Some(path) = self.startup_paths.pop_front() {
+                if std::path::Path::new(&path).exists() {
+                    self.import_file(path);
+                } else {
+                    self.show_error_dialog(&format!("File not found: {}", path));
+                }
+                ctx.request_repaint();
+            }
+        }
+
+        // バックグラウンド処理中のオーバーレイ (背後の入力はモーダルが吸収する)
+        if let Some(task) = &self.background_task {
+            egui::Modal::new(egui::Id::new("busy_overlay")).show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(&task.label);
+                });
+                if let Some(cancel) = &task.cancel {
+                    let cancelling = cancel.load(Ordering::Relaxed);
+                    if ui
+                        .add_enabled(!cancelling, egui::Button::new("Cancel"))
+                        .clicked()
+                    {
+                        cancel.store(true, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+
+        // エラーダイアログ
+        if let Some(msg) = self.error_dialog_message.clone() {
+            egui::Window::new("Error")
+                .collapsible(false)
+                .resizable(true)
+                .default_width(400.0)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    // 長いメッセージ (トレースバックなど) はスクロールして読む
+                    egui::ScrollArea::vertical()
+                        .id_salt("error_dialog_scroll")
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            ui.label(&msg);
+                        });
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("OK").clicked() {
+                            self.error_dialog_message = None;
+                        }
+                        if ui.button("Copy").clicked() {
+                            ui.ctx().copy_text(msg.clone());
+                        }
+                        if self.error_log.len() > 1 && ui.button("Show Log").clicked() {
+                            self.error_dialog_message = None;
+                            self.error_log_open = true;
+                        }
+                    });
+                });
+        }
+
+        // お知らせダイアログ
+        if let Some(msg) = self.info_dialog_message.clone() {
+            egui::Window::new("Information")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 40.0])
+                .show(ctx, |ui| {
+                    ui.label(msg);
+                    if ui.button("OK").clicked() {
+                        self.info_dialog_message = None;
+                    }
+                });
+        }
+
+        // エラーログウィンドウ
+        if self.error_log_open {
+            let error_log_open = &mut self.error_log_open;
+            let error_log = &mut self.error_log;
+            egui::Window::new("Error Log")
+                .open(error_log_open)
+                .default_width(500.0)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} error(s)", error_log.len()));
+                        if ui.button("Clear").clicked() {
+                            error_log.clear();
+                        }
+                    });
+                    ui.separator();
+                    egui::ScrollArea::vertical()
+                        .id_salt("error_log_scroll")
+                        .max_height(300.0)
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            for entry in error_log.iter() {
+                                ui.horizontal_wrapped(|ui| {
+                                    ui.monospace(&entry.time);
+                                    if entry.info {
+                                        ui.colored_label(Color32::GRAY, "[info]");
+                                    }
+                                    ui.label(&entry.message);
+                                });
+                            }
+                        });
+                });
+        }
 
-        // エラーダイアログ
-        if let Some(msg) = self.error_dialog_message.clone() {
-            egui::Window::new("Error")
+        // クリップボードからの読み込み (egui には読み取り API がないので貼り付けイベントを待つ)
+        if self.clipboard_open {
+            let pasted = ctx.input(|i| {
+                i.events.iter().find_map(|e| match e {
+                    egui::Event::Paste(text) => Some(text.clone()),
+                    _ => None,
+                })
+            });
+            if let Some(text) = pasted {
+                self.clipboard_open = false;
+                self.open_json_text(&text);
+            }
+        }
+        if self.clipboard_open {
+            egui::Window::new("Open from Clipboard")
+                .open(&mut self.clipboard_open)
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
                 .show(ctx, |ui| {
-                    ui.label(msg);
-                    if ui.button("OK").clicked() {
-                        self.error_dialog_message = None;
+                    ui.label(format!(
+                        "Press {} to load JSON log data from the clipboard.",
+                        ui.ctx().format_shortcut(&egui::KeyboardShortcut::new(
+                            egui::Modifiers::COMMAND,
+                            egui::Key::V
+                        ))
+                    ));
+                });
+        }
+
+        // バージョン情報ウィンドウ (不具合報告用にビルド情報をコピーできる)
+        if self.about_open {
+            egui::Window::new("About")
+                .open(&mut self.about_open)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    let info = format!(
+                        "{} {}\nCommit: {}\negui: {}\neframe: {}",
+                        env!("CARGO_PKG_NAME"),
+                        env!("CARGO_PKG_VERSION"),
+                        env!("GIT_COMMIT"),
+                        env!("EGUI_VERSION"),
+                        env!("EFRAME_VERSION"),
+                    );
+                    ui.monospace(&info);
+                    if ui.button("Copy").clicked() {
+                        ui.ctx().copy_text(info);
                     }
                 });
         }
 
+        // シグナル表示切り替えパレット
+        if ctx.input_mut(|i| i.consume_shortcut(&PALETTE_SHORTCUT)) {
+            self.signal_palette = SignalPaletteState {
+                open: !self.signal_palette.open,
+                ..Default::default()
+            };
+        }
+        if self.signal_palette.open {
+            self.show_signal_palette(ctx);
+        }
+
+        // ファイル結合ウィンドウ
+        if self.merge_files.open {
+            let mut open = true;
+            egui::Window::new("Merge Files")
+                .open(&mut open)
+                .show(ctx, |ui| self.show_merge_files(ui));
+            self.merge_files.open &= open;
+        }
+
+        // 無通信区間の検出ウィンドウ
+        if self.gap_finder.open {
+            let mut open = true;
+            egui::Window::new("Find Gaps")
+                .open(&mut open)
+                .default_width(500.0)
+                .show(ctx, |ui| self.show_gap_finder(ui));
+            self.gap_finder.open = open;
+        }
+
+        // イベント数ヒストグラムウィンドウ
+        if self.histogram.open {
+            let mut open = true;
+            egui::Window::new("Event Histogram")
+                .open(&mut open)
+                .default_width(600.0)
+                .show(ctx, |ui| self.show_histogram(ui));
+            self.histogram.open = open;
+        }
+
         // 変換結果ウィンドウ
         if let Some(result) = self.conversion_result.clone() {
             egui::Window::new("Conversion Result")
@@ -436,45 +5021,67 @@ impl eframe::App for MyApp {
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
                 .show(ctx, |ui| {
-                    ui.label(format!("Command: {}", result.command));
-                    ui.separator();
-                    ui.label("Standard Output:");
-                    egui::ScrollArea::vertical()
-                        .id_salt("conversion_stdout_scroll")
-                        .max_height(100.0)
-                        .show(ui, |ui| {
-                            ui.monospace(&result.stdout);
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Command: {}", result.command));
+                        if ui.button("Copy Command").clicked() {
+                            ui.ctx().copy_text(result.command.clone());
+                        }
+                    });
+                    // 出力が空のセクションは表示しない
+                    if !result.stdout.is_empty() {
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label("Standard Output:");
+                            if ui.button("Copy").clicked() {
+                                ui.ctx().copy_text(result.stdout.clone());
+                            }
                         });
-                    ui.separator();
-                    ui.label("Error Output:");
-                    egui::ScrollArea::vertical()
-                        .id_salt("conversion_stderr_scroll")
-                        .max_height(100.0)
-                        .show(ui, |ui| {
-                            ui.monospace(&result.stderr);
+                        egui::ScrollArea::vertical()
+                            .id_salt("conversion_stdout_scroll")
+                            .max_height(100.0)
+                            .show(ui, |ui| {
+                                ui.monospace(&result.stdout);
+                            });
+                    }
+                    if !result.stderr.is_empty() {
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label("Error Output:");
+                            if ui.button("Copy").clicked() {
+                                ui.ctx().copy_text(result.stderr.clone());
+                            }
                         });
+                        egui::ScrollArea::vertical()
+                            .id_salt("conversion_stderr_scroll")
+                            .max_height(100.0)
+                            .show(ui, |ui| {
+                                ui.monospace(&result.stderr);
+                            });
+                    }
                     ui.separator();
-                    ui.label(format!("Status: {}", if result.ok { "OK" } else { "NG" }));
-                    if ui.button("OK").clicked() {
-                        if result.ok {
-                            if let Some(json_path) = &result.json_file {
-                                match fs::read_to_string(json_path) {
-                                    Ok(data) => match serde_json::from_str::<DataFile>(&data) {
-                                        Ok(data_file) => {
-                                            let file_data =
-                                                FileData::from_data_file(data_file, json_path);
-                                            self.open_files.push(file_data);
-                                        }
-                                        Err(_) => {
-                                            self.show_error_dialog(
-                                                "Failed to parse JSON data as DataFile.",
-                                            );
-                                        }
-                                    },
-                                    Err(e) => {
-                                        self.show_error_dialog(&format!("File read error: {}", e));
-                                    }
+                    let status = if result.cancelled {
+                        "Cancelled"
+                    } else if result.ok {
+                        "OK"
+                    } else {
+                        "NG"
+                    };
+                    ui.label(format!("Status: {}", status));
+                    let ok_button = ui.button("OK");
+                    // Enter ですぐ閉じられるよう OK ボタンにフォーカスを置く
+                    if ui.memory(|m| m.focused().is_none()) {
+                        ok_button.request_focus();
+                    }
+                    if ok_button.clicked() {
+                        if let (true, Some(json_path)) = (result.ok, &result.json_file) {
+                            if result.temp_output {
+                                match self.converted_output.take() {
+                                    Some(Ok(file_data)) => self.add_file(file_data),
+                                    Some(Err(e)) => self.show_error_dialog(&e),
+                                    None => {}
                                 }
+                            } else {
+                                self.open_json_file(json_path);
                             }
                         }
                         self.conversion_result = None;
@@ -510,16 +5117,307 @@ impl eframe::App for MyApp {
         }
 
         // Settings ウィンドウ
+        if ctx.input_mut(|i| i.consume_shortcut(&SETTINGS_SHORTCUT)) {
+            self.settings_open = !self.settings_open;
+        }
+        let mut save_error: Option<String> = None;
         if self.settings_open {
+            // 入力欄の編集中でなければ Esc で閉じる (入力欄では Esc はフォーカス解除に使う)
+            let escape_pressed = ctx.memory(|m| m.focused().is_none())
+                && ctx.input(|i| i.key_pressed(egui::Key::Escape));
             let settings_open = &mut self.settings_open;
             let user_settings = &mut self.user_settings;
-            egui::Window::new("Settings")
+            let open_files = &mut self.open_files;
+            let confirm_reset = &mut self.confirm_reset_settings;
+            let pending_import = &mut self.pending_settings_import;
+            let window = egui::Window::new("Settings")
                 .open(settings_open)
                 .show(ctx, |ui| {
                     ui.label("Python3 Path:");
                     ui.text_edit_singleline(&mut user_settings.python_path);
+                    ui.label("Script Base Directory (empty: settings file location):");
+                    ui.text_edit_singleline(&mut user_settings.script_base_dir);
+                    ui.separator();
+                    ui.add(
+                        egui::Slider::new(&mut user_settings.time_axis_decimals, 0..=9)
+                            .text("Time axis decimals"),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Display timezone:");
+                        ui.radio_value(
+                            &mut user_settings.display_timezone,
+                            DisplayTimezone::Utc,
+                            "UTC",
+                        );
+                        ui.radio_value(
+                            &mut user_settings.display_timezone,
+                            DisplayTimezone::Local,
+                            "Local",
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("On file load:");
+                        ui.radio_value(
+                            &mut user_settings.view_on_load,
+                            ViewOnLoad::FitIfExtended,
+                            "Fit if range grows",
+                        );
+                        ui.radio_value(&mut user_settings.view_on_load, ViewOnLoad::Fit, "Fit");
+                        ui.radio_value(
+                            &mut user_settings.view_on_load,
+                            ViewOnLoad::Keep,
+                            "Keep view",
+                        );
+                    });
+                    ui.checkbox(
+                        &mut user_settings.strict_kinds,
+                        "Warn about unknown log types instead of drawing them",
+                    );
+                    ui.checkbox(
+                        &mut user_settings.tolerant_json,
+                        "Allow // and /* */ comments in JSON files",
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Drop high pulses shorter than:");
+                        ui.add(
+                            egui::DragValue::new(&mut user_settings.debounce_ms)
+                                .range(0.0..=10_000.0)
+                                .speed(0.1)
+                                .suffix(" ms"),
+                        )
+                        .on_hover_text("Filters ON/OFF chatter when files are loaded (0 disables)");
+                    });
+                    ui.checkbox(
+                        &mut user_settings.extend_unclosed_on,
+                        "Treat an ON without a matching OFF as high until the end",
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Max visible signals:");
+                        ui.add(
+                            egui::DragValue::new(&mut user_settings.max_visible_signals)
+                                .range(0..=10_000),
+                        )
+                        .on_hover_text("0 disables the limit");
+                        ui.add_enabled(
+                            user_settings.max_visible_signals > 0,
+                            egui::Checkbox::new(
+                                &mut user_settings.evict_oldest_visible,
+                                "Hide the oldest when exceeded",
+                            ),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Watch polling interval:");
+                        ui.add(
+                            egui::DragValue::new(&mut user_settings.watch_interval_ms)
+                                .range(100..=60_000)
+                                .suffix(" ms"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut user_settings.lod_enabled, "Reduce dense signals");
+                        ui.add_enabled(
+                            user_settings.lod_enabled,
+                            egui::DragValue::new(&mut user_settings.lod_max_intervals)
+                                .range(100..=1_000_000)
+                                .prefix("above ")
+                                .suffix(" intervals"),
+                        );
+                    });
+                    ui.checkbox(
+                        &mut user_settings.default_show_all,
+                        "Show all signals on load (unless default_visibility hides them)",
+                    );
+                    ui.checkbox(
+                        &mut user_settings.hide_idle_on_load,
+                        "Hide signals without edges on load (unless default_visibility shows them)",
+                    );
+                    ui.checkbox(
+                        &mut user_settings.stack_bottom_up,
+                        "Stack signals bottom-up",
+                    );
+                    ui.checkbox(
+                        &mut user_settings.lane_separators,
+                        "Draw separators between lanes",
+                    );
+                    ui.checkbox(
+                        &mut user_settings.hover_crosshair,
+                        "Show a crosshair with the time under the mouse",
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Plot background:");
+                        ui.color_edit_button_srgb(&mut user_settings.plot_background_color);
+                        ui.label("Grid lines / axis text:");
+                        ui.color_edit_button_srgb(&mut user_settings.plot_grid_color)
+                            .on_hover_text("Also used for axis tick labels and the legend");
+                        if ui.button("Default").clicked() {
+                            let defaults = UserSettings::default();
+                            user_settings.plot_background_color = defaults.plot_background_color;
+                            user_settings.plot_grid_color = defaults.plot_grid_color;
+                        }
+                    });
+                    ui.checkbox(
+                        &mut user_settings.color_by_position,
+                        "Assign colors by visible order (legacy)",
+                    );
+                    ui.separator();
+                    ui.label("Color Palette:");
+                    ui.horizontal_wrapped(|ui| {
+                        let mut remove_index = None;
+                        for (i, rgb) in user_settings.color_palette.iter_mut().enumerate() {
+                            ui.color_edit_button_srgb(rgb).context_menu(|ui| {
+                                if ui.button("Remove").clicked() {
+                                    remove_index = Some(i);
+                                    ui.close_menu();
+                                }
+                            });
+                        }
+                        if let Some(i) = remove_index {
+                            user_settings.color_palette.remove(i);
+                        }
+                        if ui.button("+").clicked() {
+                            user_settings.color_palette.push([255, 255, 255]);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Default Palette").clicked() {
+                            user_settings.color_palette = default_color_palette();
+                        }
+                        if ui.button("Colorblind-safe Palette").clicked() {
+                            user_settings.color_palette = colorblind_safe_palette();
+                        }
+                    });
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Group by field:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut user_settings.group_by_field)
+                                .hint_text("group")
+                                .desired_width(120.0),
+                        )
+                        .on_hover_text(
+                            "Name of another log field (e.g. module, thread) to group signals by",
+                        );
+                    });
+                    ui.label("Grouping Rules (regex on signal name, for logs without group):");
+                    let mut remove_rule = None;
+                    for (i, rule) in user_settings.grouping_rules.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label("Pattern:");
+                            ui.text_edit_singleline(&mut rule.pattern);
+                            ui.label("Group:");
+                            ui.text_edit_singleline(&mut rule.group);
+                            if let Err(e) = Regex::new(&rule.pattern) {
+                                ui.colored_label(Color32::RED, "Invalid")
+                                    .on_hover_text(e.to_string());
+                            }
+                            if ui.button("-").clicked() {
+                                remove_rule = Some(i);
+                            }
+                        });
+                    }
+                    if let Some(i) = remove_rule {
+                        user_settings.grouping_rules.remove(i);
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Add Rule").clicked() {
+                            user_settings.grouping_rules.push(GroupingRule {
+                                pattern: String::new(),
+                                group: String::new(),
+                            });
+                        }
+                        if ui.button("Apply to Open Files").clicked() {
+                            for file_data in open_files.iter_mut() {
+                                // recalc はシグナルを作り直すので、利用者が変えた表示設定を戻す
+                                let kept: HashMap<String, (bool, f64, f64, f32)> = file_data
+                                    .signals
+                                    .iter()
+                                    .map(|(name, sig)| {
+                                        (
+                                            name.clone(),
+                                            (
+                                                sig.visible,
+                                                sig.analog_gain,
+                                                sig.analog_offset,
+                                                sig.line_width,
+                                            ),
+                                        )
+                                    })
+                                    .collect();
+                                file_data.recalc(user_settings);
+                                for (name, sig) in file_data.signals.iter_mut() {
+                                    if let Some(&(visible, gain, offset, width)) = kept.get(name) {
+                                        sig.visible = visible;
+                                        sig.analog_gain = gain;
+                                        sig.analog_offset = offset;
+                                        sig.line_width = width;
+                                    }
+                                }
+                            }
+                        }
+                    });
+                    ui.separator();
+                    ui.label("Analog Thresholds (highlight values above):");
+                    let mut remove_threshold = None;
+                    for (i, threshold) in user_settings.analog_thresholds.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label("Signal:");
+                            ui.text_edit_singleline(&mut threshold.signal);
+                            ui.label("Above:");
+                            ui.add(egui::DragValue::new(&mut threshold.value).speed(0.1));
+                            if ui.button("-").clicked() {
+                                remove_threshold = Some(i);
+                            }
+                        });
+                    }
+                    if let Some(i) = remove_threshold {
+                        user_settings.analog_thresholds.remove(i);
+                    }
+                    if ui.button("Add Threshold").clicked() {
+                        user_settings.analog_thresholds.push(AnalogThreshold {
+                            signal: String::new(),
+                            value: 0.0,
+                        });
+                    }
+                    ui.separator();
+                    ui.label("Signal Units (displayed value = raw * scale + offset):");
+                    let mut remove_unit = None;
+                    for (i, unit) in user_settings.signal_units.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label("Signal:");
+                            ui.text_edit_singleline(&mut unit.signal);
+                            ui.label("Unit:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut unit.unit)
+                                    .hint_text("V")
+                                    .desired_width(50.0),
+                            );
+                            ui.label("Scale:");
+                            ui.add(egui::DragValue::new(&mut unit.scale).speed(0.001));
+                            ui.label("Offset:");
+                            ui.add(egui::DragValue::new(&mut unit.offset).speed(0.01));
+                            if ui.button("-").clicked() {
+                                remove_unit = Some(i);
+                            }
+                        });
+                    }
+                    if let Some(i) = remove_unit {
+                        user_settings.signal_units.remove(i);
+                    }
+                    if ui.button("Add Unit").clicked() {
+                        user_settings.signal_units.push(SignalUnit {
+                            signal: String::new(),
+                            unit: String::new(),
+                            scale: 1.0,
+                            offset: 0.0,
+                        });
+                    }
+                    ui.separator();
+                    ui.label("Environment Variables (all scripts):");
+                    env_editor(ui, "global_script_env", &mut user_settings.script_env);
                     ui.separator();
                     ui.label("Conversion Scripts:");
+                    let ambiguous = ambiguous_extensions(&user_settings.conversion_scripts);
                     let mut remove_indices = Vec::new();
                     for (i, script) in user_settings.conversion_scripts.iter_mut().enumerate() {
                         ui.horizontal(|ui| {
@@ -527,30 +5425,61 @@ impl eframe::App for MyApp {
                             ui.text_edit_singleline(&mut script.name);
                             ui.label("Script Path:");
                             ui.text_edit_singleline(&mut script.script_path);
+                            ui.label("Arguments:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut script.arguments)
+                                    .hint_text("{input}")
+                                    .desired_width(160.0),
+                            )
+                            .on_hover_text(
+                                "Placeholders: {input} = file to convert, \
+                                 {stem} = its file name without extension, \
+                                 {dir} = its directory, \
+                                 {output} = temporary JSON path (e.g. \"{input} --out {output}\")",
+                            );
                             ui.label("Extensions (comma separated):");
                             let mut ext_str = script.extensions.join(", ");
                             if ui.text_edit_singleline(&mut ext_str).changed() {
-                                script.extensions = ext_str
-                                    .split(',')
-                                    .map(|s| s.trim().to_lowercase())
-                                    .filter(|s| !s.is_empty())
-                                    .map(|s| {
-                                        if s.starts_with('.') {
-                                            s
-                                        } else {
-                                            format!(".{}", s)
-                                        }
-                                    })
-                                    .collect();
+                                script.extensions = parse_extension_list(&ext_str);
+                            }
+                            let conflicts: Vec<&str> = script
+                                .extensions
+                                .iter()
+                                .filter(|e| ambiguous.contains_key(e.as_str()))
+                                .map(|e| e.as_str())
+                                .collect();
+                            if !conflicts.is_empty() {
+                                ui.colored_label(Color32::YELLOW, "⚠")
+                                    .on_hover_text(format!(
+                                        "Also claimed by another script: {}",
+                                        conflicts.join(", ")
+                                    ));
                             }
                             if ui.button("-").clicked() {
                                 remove_indices.push(i);
                             }
                         });
+                        egui::CollapsingHeader::new(format!("Environment ({})", script.env.len()))
+                            .id_salt(("script_env", i))
+                            .show(ui, |ui| {
+                                env_editor(ui, ("script_env_rows", i), &mut script.env);
+                            });
                     }
                     for &i in remove_indices.iter().rev() {
                         user_settings.conversion_scripts.remove(i);
                     }
+                    // 同じ拡張子を複数のスクリプトが担当している場合は警告する
+                    for (ext, names) in &ambiguous {
+                        ui.colored_label(
+                            Color32::YELLOW,
+                            format!(
+                                "Warning: {} is handled by multiple scripts ({}). \
+                                 Import will ask which one to run.",
+                                ext,
+                                names.join(", ")
+                            ),
+                        );
+                    }
                     if ui.button("Add Script").clicked() {
                         user_settings
                             .conversion_scripts
@@ -558,25 +5487,131 @@ impl eframe::App for MyApp {
                                 name: "New Script".to_string(),
                                 script_path: "".to_string(),
                                 extensions: vec![],
+                                env: Vec::new(),
+                                arguments: String::new(),
                             });
                     }
-                    let mut save_error: Option<String> = None;
-                    if ui.button("Save Settings").clicked() {
-                        match serde_json::to_string_pretty(&*user_settings) {
-                            Ok(content) => {
-                                if let Err(e) = fs::write("user_settings.json", content) {
-                                    save_error = Some(format!("Failed to save settings: {}", e));
+                    ui.checkbox(&mut user_settings.auto_save, "Auto-save")
+                        .on_hover_text(format!(
+                            "Write {} whenever a setting changes",
+                            SETTINGS_FILE
+                        ));
+                    ui.horizontal(|ui| {
+                        if !user_settings.auto_save && ui.button("Save Settings").clicked() {
+                            save_error = Self::save_settings(user_settings).err();
+                        }
+                        // 外部で編集した設定ファイルを読み直す
+                        if ui
+                            .button("Reload Settings")
+                            .on_hover_text(format!("Re-read {} from disk", SETTINGS_FILE))
+                            .clicked()
+                        {
+                            match Self::load_settings() {
+                                Ok(settings) => *user_settings = settings,
+                                Err(e) => {
+                                    save_error =
+                                        Some(format!("Failed to reload {}: {}", SETTINGS_FILE, e));
                                 }
                             }
-                            Err(e) => {
-                                save_error = Some(format!("Failed to serialize settings: {}", e));
+                        }
+                        // 誤操作で設定を失わないよう、確認してから初期化する
+                        if *confirm_reset {
+                            ui.label("Are you sure?");
+                            if ui.button("Yes, reset").clicked() {
+                                *user_settings = UserSettings::default();
+                                save_error = Self::save_settings(user_settings).err();
+                                *confirm_reset = false;
+                            }
+                            if ui.button("Cancel").clicked() {
+                                *confirm_reset = false;
                             }
+                        } else if ui.button("Reset to Defaults").clicked() {
+                            *confirm_reset = true;
                         }
-                    }
-                    if let Some(err) = save_error {
-                        self.error_dialog_message = Some(err);
+                    });
+                    // チームで共有する設定ファイルの書き出し/読み込み
+                    ui.horizontal(|ui| {
+                        if ui.button("Export Settings...").clicked() {
+                            if let Some(path) = FileDialog::new()
+                                .add_filter("JSON", &["json"])
+                                .set_file_name(SETTINGS_FILE)
+                                .save_file()
+                            {
+                                let result = serde_json::to_string_pretty(&*user_settings)
+                                    .map_err(|e| e.to_string())
+                                    .and_then(|c| fs::write(&path, c).map_err(|e| e.to_string()));
+                                if let Err(e) = result {
+                                    save_error = Some(format!("Failed to export settings: {}", e));
+                                }
+                            }
+                        }
+                        if ui.button("Import Settings...").clicked() {
+                            if let Some(path) =
+                                FileDialog::new().add_filter("JSON", &["json"]).pick_file()
+                            {
+                                match read_settings_file(&path) {
+                                    Ok(imported) => *pending_import = Some(imported),
+                                    Err(e) => {
+                                        save_error =
+                                            Some(format!("Failed to import settings: {}", e));
+                                    }
+                                }
+                            }
+                        }
+                    });
+                    if pending_import.is_some() {
+                        ui.horizontal(|ui| {
+                            ui.label("Apply imported settings?");
+                            if ui
+                                .button("Replace All")
+                                .on_hover_text("Replace every setting with the imported file")
+                                .clicked()
+                            {
+                                *user_settings = pending_import.take().unwrap_or_default();
+                            } else if ui
+                                .button("Merge Conversion Settings")
+                                .on_hover_text(
+                                    "Add the imported Python path, scripts, environment \
+                                     variables and grouping rules; keep display settings",
+                                )
+                                .clicked()
+                            {
+                                if let Some(imported) = pending_import.take() {
+                                    user_settings.merge_conversion_settings(imported);
+                                }
+                            } else if ui.button("Cancel").clicked() {
+                                *pending_import = None;
+                            }
+                        });
                     }
                 });
+            // 最前面の Settings ウィンドウだけを閉じる
+            if let Some(window) = window {
+                let top_layer = ctx.memory(|m| m.areas().top_layer_id(egui::Order::Middle));
+                if escape_pressed && top_layer == Some(window.response.layer_id) {
+                    *settings_open = false;
+                }
+            }
+        }
+        // 自動保存: 最後の変更から SETTINGS_AUTO_SAVE_DELAY 秒たったら書き込む
+        if self.user_settings.auto_save {
+            let now = ctx.input(|i| i.time);
+            let snapshot = serde_json::to_string(&self.user_settings).unwrap_or_default();
+            if snapshot != self.settings_snapshot {
+                self.settings_snapshot = snapshot;
+                self.settings_save_due = Some(now + SETTINGS_AUTO_SAVE_DELAY);
+            }
+            if let Some(due) = self.settings_save_due {
+                if now >= due {
+                    self.settings_save_due = None;
+                    save_error = Self::save_settings(&self.user_settings).err();
+                } else {
+                    ctx.request_repaint_after(std::time::Duration::from_secs_f64(due - now));
+                }
+            }
+        }
+        if let Some(err) = save_error {
+            self.show_error_dialog(&err);
         }
 
         // メニューバー
@@ -588,265 +5623,803 @@ impl eframe::App for MyApp {
                         if let Some(path) = FileDialog::new().pick_file() {
                             let path_str = path.to_string_lossy().to_string();
                             if path_str.to_lowercase().ends_with(".json") {
-                                match fs::read_to_string(&path_str) {
-                                    Ok(data) => match serde_json::from_str::<DataFile>(&data) {
-                                        Ok(data_file) => {
-                                            let file_data =
-                                                FileData::from_data_file(data_file, &path_str);
-                                            self.open_files.push(file_data);
-                                        }
-                                        Err(_) => {
-                                            self.show_error_dialog(
-                                                "Failed to parse JSON data as DataFile.",
-                                            );
-                                        }
-                                    },
-                                    Err(e) => {
-                                        self.show_error_dialog(&format!("File read error: {}", e));
-                                    }
-                                }
+                                self.open_json_file(&path_str);
                             } else {
                                 self.show_error_dialog("Open only supports .json files.");
                             }
                         }
                     }
 
+                    if ui.button("Open from Clipboard").clicked() {
+                        ui.close_menu();
+                        self.clipboard_open = true;
+                    }
+
+                    if ui.button("Open Folder").clicked() {
+                        ui.close_menu();
+                        self.open_folder(false);
+                    }
+
+                    if ui.button("Open Folder (Recursive)").clicked() {
+                        ui.close_menu();
+                        self.open_folder(true);
+                    }
+
                     if ui.button("Import").clicked() {
                         ui.close_menu();
                         if let Some(path) = FileDialog::new().pick_file() {
-                            let path_str = path.to_string_lossy().to_string();
-                            if path_str.to_lowercase().ends_with(".json") {
-                                match fs::read_to_string(&path_str) {
-                                    Ok(data) => match serde_json::from_str::<DataFile>(&data) {
-                                        Ok(data_file) => {
-                                            let file_data =
-                                                FileData::from_data_file(data_file, &path_str);
-                                            self.open_files.push(file_data);
-                                        }
-                                        Err(_) => {
-                                            self.show_error_dialog(
-                                                "Failed to parse JSON data as DataFile.",
-                                            );
-                                        }
-                                    },
-                                    Err(e) => {
-                                        self.show_error_dialog(&format!("File read error: {}", e));
-                                    }
-                                }
-                            } else {
-                                let ext = std::path::Path::new(&path_str)
-                                    .extension()
-                                    .and_then(|s| s.to_str())
-                                    .unwrap_or("")
-                                    .to_lowercase();
-                                let ext_with_dot = if !ext.is_empty() {
-                                    format!(".{}", ext)
-                                } else {
-                                    "".to_string()
-                                };
-                                let candidates: Vec<_> = self
-                                    .user_settings
-                                    .conversion_scripts
-                                    .iter()
-                                    .cloned()
-                                    .filter(|script| {
-                                        script
-                                            .extensions
-                                            .iter()
-                                            .any(|e| e.to_lowercase() == ext_with_dot)
-                                    })
-                                    .collect();
-                                if candidates.is_empty() {
-                                    self.show_error_dialog(&format!(
-                                        "拡張子 {} に対応する変換スクリプトが設定されていません。",
-                                        ext_with_dot
-                                    ));
-                                } else if candidates.len() == 1 {
-                                    self.execute_conversion(&path_str, candidates[0].clone());
-                                } else {
-                                    self.pending_import_file = Some(path_str);
-                                    self.pending_script_candidates = Some(candidates);
-                                }
-                            }
+                            self.import_file(path.to_string_lossy().to_string());
                         }
                     }
 
+                    if ui
+                        .add_enabled(
+                            self.open_files.len() > 1,
+                            egui::Button::new("Merge Files..."),
+                        )
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.merge_files = MergeFilesState {
+                            open: true,
+                            entries: vec![(false, 0.0); self.open_files.len()],
+                        };
+                    }
+
+                    if ui.button("Save As...").clicked() {
+                        ui.close_menu();
+                        self.save_file_as();
+                    }
+
+                    if ui.button("Export Report").clicked() {
+                        ui.close_menu();
+                        self.export_report();
+                    }
+
+                    if ui.button("Export Intervals").clicked() {
+                        ui.close_menu();
+                        self.export_intervals();
+                    }
+
+                    // 描画内容が必要なので、次のフレームの中央ペインで書き出す
+                    if ui
+                        .add_enabled(
+                            !self.open_files.is_empty(),
+                            egui::Button::new("Export SVG..."),
+                        )
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.export_svg_pending = true;
+                    }
+
                     if ui.button("Exit").clicked() {
                         std::process::exit(0);
                     }
                 });
-                if ui.button("Settings").clicked() {
-                    self.settings_open = true;
+                if ui
+                    .selectable_label(self.settings_open, "Settings")
+                    .on_hover_text(ctx.format_shortcut(&SETTINGS_SHORTCUT))
+                    .clicked()
+                {
+                    self.settings_open = !self.settings_open;
+                }
+                ui.checkbox(&mut self.relative_time, "Relative Time");
+                if ui
+                    .checkbox(&mut self.watch_files, "Watch Files")
+                    .on_hover_text("Reload open files when they change on disk")
+                    .changed()
+                {
+                    self.last_watch_check = 0.0;
+                    self.watch_new_events = 0;
+                    self.watch_event_rate = None;
+                }
+                if ui
+                    .selectable_label(self.log_table.open, "Log Table")
+                    .clicked()
+                {
+                    self.log_table.open = !self.log_table.open;
                 }
+                if ui
+                    .selectable_label(self.histogram.open, "Histogram")
+                    .clicked()
+                {
+                    self.histogram.open = !self.histogram.open;
+                }
+                if ui
+                    .selectable_label(self.gap_finder.open, "Gaps")
+                    .on_hover_text("Find periods with no events across all files")
+                    .clicked()
+                {
+                    self.gap_finder.open = !self.gap_finder.open;
+                }
+                if ui
+                    .selectable_label(self.signal_palette.open, "Toggle Signal")
+                    .on_hover_text(ctx.format_shortcut(&PALETTE_SHORTCUT))
+                    .clicked()
+                {
+                    self.signal_palette = SignalPaletteState {
+                        open: !self.signal_palette.open,
+                        ..Default::default()
+                    };
+                }
+                if ui
+                    .selectable_label(self.inspector_open, "Inspector")
+                    .on_hover_text("List signal states at the cursor (click the plot to place it)")
+                    .clicked()
+                {
+                    self.inspector_open = !self.inspector_open;
+                }
+                let error_log_label = if self.error_log.is_empty() {
+                    "Error Log".to_string()
+                } else {
+                    format!("Error Log ({})", self.error_log.len())
+                };
+                if ui.button(error_log_label).clicked() {
+                    self.error_log_open = true;
+                }
+                ui.menu_button("Help", |ui| {
+                    if ui.button("About").clicked() {
+                        ui.close_menu();
+                        self.about_open = true;
+                    }
+                });
             });
         });
 
-        // 左側ペイン：各ファイルごとのシグナルツリー表示
-        egui::SidePanel::left("group_panel")
-            .resizable(true)
-            .show(ctx, |ui| {
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    if self.open_files.is_empty() {
-                        ui.label("No file loaded.");
-                    } else {
-                        for file_data in &mut self.open_files {
-                            egui::CollapsingHeader::new(&file_data.file_name)
-                                .default_open(true)
-                                .show(ui, |ui| {
-                                    let file_all_visible =
-                                        file_data.signals.values().all(|sig| sig.visible);
-                                    let mut file_toggle = file_all_visible;
-                                    if ui.checkbox(&mut file_toggle, "Toggle All").changed() {
-                                        for sig in file_data.signals.values_mut() {
-                                            sig.visible = file_toggle;
-                                        }
-                                    }
-                                    let mut group_keys: Vec<String> =
-                                        file_data.groups.keys().cloned().collect();
-                                    group_keys.sort();
-                                    for group_key in group_keys {
-                                        if let Some(group) = file_data.groups.get(&group_key) {
-                                            let group_all_visible = group
-                                                .signals
-                                                .iter()
-                                                .all(|s| file_data.signals[s].visible);
-                                            egui::CollapsingHeader::new(&group.name)
-                                                .default_open(false)
-                                                .show(ui, |ui| {
-                                                    let mut group_toggle = group_all_visible;
-                                                    if ui
-                                                        .checkbox(&mut group_toggle, "Toggle All")
-                                                        .changed()
-                                                    {
-                                                        for s in &group.signals {
-                                                            if let Some(sig) =
-                                                                file_data.signals.get_mut(s)
-                                                            {
-                                                                sig.visible = group_toggle;
-                                                            }
-                                                        }
-                                                    }
-                                                    ui.indent("group_signals", |ui| {
-                                                        for s in &group.signals {
-                                                            if let Some(sig) =
-                                                                file_data.signals.get_mut(s)
-                                                            {
-                                                                let mut check = sig.visible;
-                                                                if ui
-                                                                    .checkbox(&mut check, &sig.name)
-                                                                    .changed()
-                                                                {
-                                                                    sig.visible = check;
-                                                                }
-                                                            }
-                                                        }
-                                                    });
-                                                });
-                                            ui.separator();
-                                        }
-                                    }
-                                });
+        // 設定ファイルを読めなかったことを知らせるバナー
+        if let Some(message) = &self.settings_warning {
+            let mut dismiss = false;
+            egui::TopBottomPanel::top("settings_warning").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(Color32::YELLOW, "⚠");
+                    ui.label(message);
+                    if ui.button("Dismiss").clicked() {
+                        dismiss = true;
+                    }
+                });
+            });
+            if dismiss {
+                self.settings_warning = None;
+            }
+        }
+
+        // ステータスバー：ファイル数・シグナル数・可視数・時間範囲
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let total_signals: usize = self.open_files.iter().map(|f| f.signals.len()).sum();
+                let visible_signals: usize = self
+                    .open_files
+                    .iter()
+                    .map(|f| f.signals.values().filter(|s| s.visible).count())
+                    .sum();
+                ui.label(format!("Files: {}", self.open_files.len()));
+                ui.separator();
+                ui.label(format!("Signals: {}", total_signals));
+                ui.separator();
+                ui.label(format!("Visible: {}", visible_signals));
+                if !self.open_files.is_empty() {
+                    let (min_t, max_t) = self.global_time_range();
+                    ui.separator();
+                    ui.label(format!(
+                        "Span: {} - {} ({:.3} s)",
+                        format_iso(min_t),
+                        format_iso(max_t),
+                        max_t - min_t
+                    ));
+                }
+                if self.watch_files {
+                    ui.separator();
+                    let rate = self
+                        .watch_event_rate
+                        .map(|r| format!("{:.1}/s", r))
+                        .unwrap_or_else(|| "-".to_string());
+                    ui.label(format!("New events: {} ({})", self.watch_new_events, rate))
+                        .on_hover_text("Entries added since watching started, and the rate over the last polling interval");
+                }
+            });
+        });
+
+        // 下部パネル：選択ファイルのログエントリ一覧
+        if self.log_table.open {
+            egui::TopBottomPanel::bottom("log_table_panel")
+                .resizable(true)
+                .default_height(220.0)
+                .show(ctx, |ui| {
+                    self.show_log_table(ui);
+                });
+        }
+
+        // 左側ペイン：各ファイルごとのシグナルツリー表示
+        egui::SidePanel::left("group_panel")
+            .resizable(true)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if self.open_files.is_empty() {
+                        ui.label("No file loaded.");
+                    } else {
+                        ui.checkbox(&mut self.merge_groups, "Merge groups across files");
+                        ui.checkbox(&mut self.hide_idle, "Hide idle signals");
+                        ui.separator();
+                        // 強調表示の期限が切れたら解除する
+                        let now = ui.input(|i| i.time);
+                        if self.tree_focus.as_ref().is_some_and(|f| now > f.until) {
+                            self.tree_focus = None;
+                        } else if self.tree_focus.is_some() {
+                            ui.ctx().request_repaint();
+                        }
+                        self.show_selection_bar(ui);
+                        self.show_pinned_signals(ui);
+                        let mut tree_error = None;
+                        if self.merge_groups {
+                            self.show_merged_group_tree(ui);
+                        } else {
+                            let file_names: Vec<String> = self
+                                .open_files
+                                .iter()
+                                .map(|f| f.file_name.clone())
+                                .collect();
+                            // 「Diff with」には同名シグナルを持つファイルだけを出す
+                            let file_signals: Vec<HashSet<String>> = self
+                                .open_files
+                                .iter()
+                                .map(|f| f.signals.keys().cloned().collect())
+                                .collect();
+                            for (file_index, file_data) in self.open_files.iter_mut().enumerate() {
+                                let focus = self
+                                    .tree_focus
+                                    .as_mut()
+                                    .filter(|f| f.file_index == file_index);
+                                if let Some(e) = Self::show_file_tree(
+                                    ui,
+                                    file_index,
+                                    file_data,
+                                    focus,
+                                    &mut self.selection,
+                                    FileTreeContext {
+                                        pinned: &mut self.pinned,
+                                        solo_group: &mut self.solo_group,
+                                        file_names: &file_names,
+                                        file_signals: &file_signals,
+                                    },
+                                ) {
+                                    tree_error = Some(e);
+                                }
+                            }
+                        }
+                        if let Some(e) = tree_error {
+                            self.show_error_dialog(&e);
                         }
                     }
                 });
             });
 
+        // 右ペイン：カーソル位置での各シグナルの状態
+        if self.inspector_open {
+            egui::SidePanel::right("inspector_panel")
+                .resizable(true)
+                .show(ctx, |ui| {
+                    self.show_inspector(ui);
+                });
+        }
+
         // 中央ペイン：全ファイル・全グループ・全シグナルを左ペインと同じ順で列挙し、
         // 可視のものだけ順番に上から詰めて描画する
         egui::CentralPanel::default().show(ctx, |ui| {
-            // グローバルな時刻範囲を計算
-            let global_min_time = self
-                .open_files
-                .iter()
-                .map(|f| f.min_time)
-                .fold(f64::INFINITY, f64::min);
-            let global_max_time = self
-                .open_files
-                .iter()
-                .map(|f| f.max_time)
-                .fold(0.0, f64::max);
-            let global_min_time = if global_min_time == f64::INFINITY {
-                0.0
-            } else {
-                global_min_time
-            };
-            let global_max_time = if global_max_time == 0.0 {
-                10.0
-            } else {
-                global_max_time
+            // タブ：None は全ファイルを重ねて表示、Some(i) は i 番目のファイルのみ
+            if self.active_tab.is_some_and(|i| i >= self.open_files.len()) {
+                self.active_tab = None;
+            }
+            if !self.open_files.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    if ui
+                        .selectable_label(self.active_tab.is_none(), "All Files")
+                        .clicked()
+                    {
+                        self.active_tab = None;
+                    }
+                    for (i, file_data) in self.open_files.iter().enumerate() {
+                        if ui
+                            .selectable_label(self.active_tab == Some(i), &file_data.file_name)
+                            .clicked()
+                        {
+                            self.active_tab = Some(i);
+                        }
+                    }
+                });
+                ui.separator();
+            }
+            let active_tab = self.active_tab;
+
+            // ツールバー (ズームリセット・値検索)
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Reset Zoom")
+                    .on_hover_text("Right-drag on the plot to zoom into a box")
+                    .clicked()
+                {
+                    self.reset_plot_view = true;
+                }
+                let copy_shortcut = ui.input_mut(|i| i.consume_shortcut(&COPY_PLOT_SHORTCUT));
+                if ui
+                    .button("Copy Plot")
+                    .on_hover_text(format!(
+                        "Copy the plot as an image ({})",
+                        ui.ctx().format_shortcut(&COPY_PLOT_SHORTCUT)
+                    ))
+                    .clicked()
+                    || copy_shortcut
+                {
+                    self.request_plot_copy(ui.ctx());
+                }
+                ui.checkbox(&mut self.fill_high, "Filled")
+                    .on_hover_text("Shade the high portion of each lane");
+                ui.checkbox(&mut self.fixed_lanes, "Fixed Lanes")
+                    .on_hover_text(
+                    "Keep a lane for every signal so toggling visibility doesn't shift the others",
+                );
+                ui.checkbox(&mut self.show_group_labels, "Group Labels")
+                    .on_hover_text("Label lanes as file / group / signal");
+                // 表示座標が変わるので切り替えたら表示範囲を合わせ直す
+                if ui
+                    .checkbox(&mut self.compress_idle, "Compress Idle")
+                    .on_hover_text("Collapse gaps with no events to a narrow band")
+                    .changed()
+                {
+                    self.reset_plot_view = true;
+                }
+                if ui
+                    .add_enabled(
+                        self.compress_idle,
+                        egui::DragValue::new(&mut self.compress_gap)
+                            .range(0.001..=3600.0)
+                            .speed(0.01)
+                            .prefix("> ")
+                            .suffix(" s"),
+                    )
+                    .changed()
+                {
+                    self.reset_plot_view = true;
+                }
+                if let Some((file_index, group)) = &self.solo_group {
+                    let file_name = self
+                        .open_files
+                        .get(*file_index)
+                        .map(|f| f.file_name.as_str())
+                        .unwrap_or("?");
+                    ui.separator();
+                    ui.colored_label(Color32::YELLOW, format!("Solo: {} / {}", file_name, group));
+                    if ui.button("Clear Solo").clicked() {
+                        self.solo_group = None;
+                    }
+                }
+                ui.separator();
+                ui.label("Go to:");
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.goto_input)
+                        .hint_text("HH:MM:SS.mmm")
+                        .desired_width(110.0),
+                );
+                if response.changed() {
+                    self.goto_invalid = false;
+                }
+                let submitted =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui.button("Go").clicked() || submitted) && !self.goto_input.is_empty() {
+                    let (reference, _) = match self.active_tab {
+                        Some(i) => (self.open_files[i].min_time, self.open_files[i].max_time),
+                        None => self.global_time_range(),
+                    };
+                    match parse_time_input(
+                        &self.goto_input,
+                        reference,
+                        self.user_settings.display_timezone,
+                    ) {
+                        Some(t) => {
+                            self.goto_time = Some(t);
+                            self.goto_invalid = false;
+                        }
+                        None => self.goto_invalid = true,
+                    }
+                }
+                if self.goto_invalid {
+                    ui.colored_label(Color32::RED, "Invalid time");
+                }
+                ui.separator();
+                ui.label("Value search:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.value_search)
+                        .hint_text("e.g. ERR_42")
+                        .desired_width(160.0),
+                );
+                ui.checkbox(&mut self.value_search_exact, "Exact");
+                if !self.value_search.is_empty() && ui.button("Clear").clicked() {
+                    self.value_search.clear();
+                }
+                if let Some(count) = self.visible_limit_exceeded {
+                    ui.separator();
+                    ui.colored_label(
+                        Color32::YELLOW,
+                        format!(
+                            "{} signals visible (limit {})",
+                            count, self.user_settings.max_visible_signals
+                        ),
+                    );
+                }
+            });
+
+            // 時刻範囲を計算 (タブ選択時はそのファイルの範囲)
+            let (global_min_time, global_max_time) = match active_tab {
+                Some(i) => (self.open_files[i].min_time, self.open_files[i].max_time),
+                None => self.global_time_range(),
             };
 
             // 左ペインの順序と同じく「ファイル→グループ→シグナル」で可視シグナルを抽出
             // → 上から順にオフセットを割り当てる
-            let mut visible_signals = Vec::new(); // (label, color, intervals)
-            let mut file_index = 0;
-            let color_palette = [
-                Color32::RED,
-                Color32::GREEN,
-                Color32::BLUE,
-                Color32::YELLOW,
-                Color32::LIGHT_BLUE,
-                Color32::LIGHT_GREEN,
-                Color32::WHITE,
-                Color32::GOLD,
-            ];
+            let mut visible_signals: Vec<PlotSignal> = Vec::new();
+            let palette = if self.user_settings.color_palette.is_empty() {
+                default_color_palette()
+            } else {
+                self.user_settings.color_palette.clone()
+            };
+            let color_palette: Vec<Color32> = palette
+                .iter()
+                .map(|[r, g, b]| Color32::from_rgb(*r, *g, *b))
+                .collect();
 
-            for file_data in &self.open_files {
-                let mut group_keys: Vec<String> = file_data.groups.keys().cloned().collect();
-                group_keys.sort();
+            let color_by_position = self.user_settings.color_by_position;
+            let value_query = self.value_search.trim();
+            let value_search_exact = self.value_search_exact;
+            let hide_idle = self.hide_idle;
+            // レーン固定モードでは非表示のシグナルにもレーンを割り当て、
+            // 表示を切り替えても他のレーンの位置が変わらないようにする
+            let fixed_lanes = self.fixed_lanes;
+            let show_group_labels = self.show_group_labels;
+            // ソロ表示中は対象グループのシグナルだけを、表示状態に関係なく並べる
+            let solo_group = self.solo_group.clone();
+            let mut lane_count = 0;
+            // ピン留めしたシグナルは通常の並びとは別に数え、最後に一番上へ並べる
+            let mut pinned_signals: Vec<PlotSignal> = Vec::new();
+            let mut pinned_count = 0;
+            let file_names: Vec<String> = self
+                .open_files
+                .iter()
+                .map(|f| f.file_name.clone())
+                .collect();
+            for (file_index, file_data) in self.open_files.iter().enumerate() {
+                if active_tab.is_some_and(|t| t != file_index) {
+                    continue;
+                }
+                if !file_data.enabled || solo_group.as_ref().is_some_and(|(f, _)| *f != file_index)
+                {
+                    continue;
+                }
+                // 値検索に一致したログをシグナル名ごとにまとめる
+                let mut search_hits: HashMap<&str, Vec<f64>> = HashMap::new();
+                if !value_query.is_empty() {
+                    for log in &file_data.logs {
+                        if value_matches(&log.value, value_query, value_search_exact) {
+                            search_hits
+                                .entry(log.name.as_str())
+                                .or_default()
+                                .push(log.timestamp_num);
+                        }
+                    }
+                }
+                let group_keys = file_data.ordered_group_keys();
                 // 好みで、ファイル名を色分けの単位にするならここでリセットしてもよい
                 // 例: let mut color_idx = 0;
                 for group_key in group_keys {
+                    if solo_group.as_ref().is_some_and(|(_, g)| *g != group_key) {
+                        continue;
+                    }
                     if let Some(group) = file_data.groups.get(&group_key) {
-                        for s in &group.signals {
+                        for (member_idx, s) in group.signals.iter().enumerate() {
                             if let Some(sig) = file_data.signals.get(s) {
-                                if sig.visible {
+                                let shown = solo_group.is_some()
+                                    || (sig.visible && !(hide_idle && sig.idle));
+                                let pinned = self.pinned.contains(&(file_index, s.clone()));
+                                let counter = if pinned {
+                                    &mut pinned_count
+                                } else {
+                                    &mut lane_count
+                                };
+                                let lane = *counter;
+                                if shown || fixed_lanes {
+                                    *counter += 1;
+                                }
+                                if shown {
                                     // signal の表示ラベルは "ファイル名 → シグナル名" などお好みで
-                                    let label = format!("{} / {}", file_data.file_name, sig.name);
-                                    // ここではシグナルごとに適当にパレットから色を取る例
-                                    // 実際にはシグナル固有の色があればそれを使っても良い
-                                    // 例: let color_idx = (file_index + ???) % color_palette.len();
-                                    let color_idx =
-                                        (file_index + visible_signals.len()) % color_palette.len();
-                                    let color = color_palette[color_idx];
-                                    visible_signals.push((label, color, &sig.on_intervals));
+                                    let label = if show_group_labels {
+                                        format!(
+                                            "{} / {} / {}",
+                                            file_data.file_name, group_key, sig.name
+                                        )
+                                    } else {
+                                        format!("{} / {}", file_data.file_name, sig.name)
+                                    };
+                                    // 単位を設定したシグナルはラベルに単位を添える
+                                    let label = match find_signal_unit(
+                                        &self.user_settings.signal_units,
+                                        &sig.name,
+                                    ) {
+                                        Some(unit) if !unit.unit.is_empty() => {
+                                            format!("{} [{}]", label, unit.unit)
+                                        }
+                                        _ => label,
+                                    };
+                                    // 個別に設定した色 → グループ色 → パレットの順に決める
+                                    let color = if let Some(color) = file_data.signal_colors.get(s)
+                                    {
+                                        *color
+                                    } else if let Some(base) = group.color {
+                                        group_member_color(base, member_idx, group.signals.len())
+                                    } else if color_by_position {
+                                        let color_idx = (file_index + visible_signals.len())
+                                            % color_palette.len();
+                                        color_palette[color_idx]
+                                    } else {
+                                        let key = format!("{}/{}", file_data.file_name, sig.name);
+                                        color_palette
+                                            [stable_palette_index(&key, color_palette.len())]
+                                    };
+                                    let plot_signal = PlotSignal {
+                                        label,
+                                        color,
+                                        sig,
+                                        file_index,
+                                        lane,
+                                        derived_intervals: None,
+                                        search_hits: search_hits
+                                            .remove(sig.name.as_str())
+                                            .unwrap_or_default(),
+                                    };
+                                    if pinned {
+                                        pinned_signals.push(plot_signal);
+                                    } else {
+                                        visible_signals.push(plot_signal);
+                                    }
                                 }
                             }
                         }
                     }
                 }
-                file_index += 1;
+                // 派生シグナルはファイルの末尾に並べる (ソロ表示中は出さない)
+                for derived in file_data.derived.iter().filter(|_| solo_group.is_none()) {
+                    if let Some(sig) = file_data.signals.get(&derived.source) {
+                        let lane = lane_count;
+                        if derived.visible || fixed_lanes {
+                            lane_count += 1;
+                        }
+                        if !derived.visible {
+                            continue;
+                        }
+                        let label =
+                            format!("{} / {}", file_data.file_name, derived.label(&file_names));
+                        let color =
+                            color_palette[stable_palette_index(&label, color_palette.len())];
+                        visible_signals.push(PlotSignal {
+                            label,
+                            color,
+                            sig,
+                            file_index,
+                            lane,
+                            derived_intervals: Some(derived.apply(
+                                sig,
+                                global_min_time,
+                                global_max_time,
+                                &self.open_files,
+                            )),
+                            search_hits: Vec::new(),
+                        });
+                    }
+                }
+            }
+
+            // ピン留めしたシグナルのレーンを先頭にし、残りをその後ろにずらす
+            for plot_signal in &mut visible_signals {
+                plot_signal.lane += pinned_count;
             }
+            pinned_signals.append(&mut visible_signals);
+            let visible_signals = pinned_signals;
 
             // 上から詰めて描画するためにオフセットを割り当てる
             // 一番上が visible_signals[0]、次が visible_signals[1] ... という風に
             // ここでは「上を大きい数字、下を小さい数字」にする場合は逆順にしても良い
-            let total = visible_signals.len();
+            let total = pinned_count + lane_count;
+            // 前フレームの表示範囲から 1 ピクセルあたりの時間幅を求める (LOD 用)
+            let lod_max_intervals = self.user_settings.lod_max_intervals;
+            let lod_pixel_width = self
+                .plot_view
+                .filter(|_| self.user_settings.lod_enabled)
+                .filter(|(_, _, width)| *width > 0.0)
+                .map(|(x_min, x_max, width)| (x_max - x_min) / width as f64);
+            let stack_bottom_up = self.user_settings.stack_bottom_up;
+            // 長い無通信区間を縮めて表示する (縮めた幅は閾値の 1/10)
+            let warp = if self.compress_idle {
+                let times = self.event_times(active_tab);
+                TimeWarp::from_times(&times, self.compress_gap, self.compress_gap / 10.0)
+            } else {
+                TimeWarp::default()
+            };
             let mut offset_map = HashMap::new(); // y軸ラベル用
+            let mut lanes = Vec::new(); // クリック判定用 (y_offset, ファイル番号, シグナル名)
             let mut lines_to_draw = Vec::new();
-            for (i, (label, color, intervals)) in visible_signals.into_iter().enumerate() {
-                // i=0 を最上にする → y_offset = (total - i) * 2 - 1
-                let y_offset = ((total - i) * 2 - 1) as f64;
+            // High 区間の塗りつぶし (Filled 表示時のみ)
+            let fill_high = self.fill_high;
+            let mut fills_to_draw = Vec::new();
+            let mut state_tracks = Vec::new();
+            let mut analog_tracks = Vec::new();
+            let user_settings = &self.user_settings;
+            let mut search_markers = Vec::new();
+            let export_svg = std::mem::take(&mut self.export_svg_pending);
+            let mut svg_figure = SvgFigure::default();
+            for plot_signal in visible_signals {
+                let PlotSignal {
+                    label,
+                    color,
+                    sig,
+                    file_index,
+                    lane,
+                    derived_intervals,
+                    search_hits,
+                } = plot_signal;
+                // lane=0 を最上にする → y_offset = (total - lane) * 2 - 1
+                // 下から積む設定では lane=0 を最下にする → y_offset = (lane + 1) * 2 - 1
+                let slot = if stack_bottom_up {
+                    lane + 1
+                } else {
+                    total - lane
+                };
+                let y_offset = (slot * 2 - 1) as f64;
+                lanes.push((y_offset, file_index, sig.name.clone()));
                 offset_map.insert(y_offset.round() as i32, label.clone());
+                for t in search_hits {
+                    search_markers.push([warp.to_display(t), y_offset + 0.5]);
+                }
+                if export_svg {
+                    svg_figure.labels.push((y_offset, label.clone()));
+                }
 
-                let line =
-                    Self::build_digital_wave(intervals, global_min_time, global_max_time, y_offset)
-                        .color(color)
-                        .width(2.0)
-                        .name(label);
+                // 文字列の状態値を持つシグナルはラベル付きボックスで描画する
+                if derived_intervals.is_none() && !sig.state_samples.is_empty() {
+                    if export_svg {
+                        let samples = &sig.state_samples;
+                        for (i, (start, value)) in samples.iter().enumerate() {
+                            let end = samples
+                                .get(i + 1)
+                                .map(|(t, _)| *t)
+                                .unwrap_or(global_max_time);
+                            if end > *start {
+                                svg_figure.boxes.push((
+                                    color,
+                                    warp.to_display(*start),
+                                    warp.to_display(end),
+                                    y_offset,
+                                    value.clone(),
+                                ));
+                            }
+                        }
+                    }
+                    state_tracks.push((label, color, &sig.state_samples, y_offset));
+                    continue;
+                }
+                // 数値のシグナルはレーン内に正規化した折れ線で描画する
+                if derived_intervals.is_none() && !sig.analog_samples.is_empty() {
+                    if export_svg {
+                        let points = sig
+                            .analog_samples
+                            .iter()
+                            .map(|(t, v)| [warp.to_display(*t), analog_lane_y(sig, y_offset, *v)])
+                            .collect();
+                        svg_figure.lines.push((color, sig.line_width, points));
+                    }
+                    analog_tracks.push((label, color, sig, y_offset));
+                    continue;
+                }
+                let intervals = derived_intervals.as_deref().unwrap_or(&sig.on_intervals);
+                // 区間が多すぎる場合は 1 ピクセル未満の隙間をまとめて描画点数を減らす
+                let reduced;
+                let intervals = match lod_pixel_width {
+                    Some(pixel) if intervals.len() > lod_max_intervals => {
+                        reduced = downsample_intervals(intervals, pixel);
+                        &reduced[..]
+                    }
+                    _ => intervals,
+                };
+                let warped;
+                let intervals = if warp.gaps.is_empty() {
+                    intervals
+                } else {
+                    warped = warp.warp_intervals(intervals);
+                    &warped[..]
+                };
+                if fill_high {
+                    for iv in intervals {
+                        let rect = vec![
+                            [iv.start, y_offset],
+                            [iv.end, y_offset],
+                            [iv.end, y_offset + 1.0],
+                            [iv.start, y_offset + 1.0],
+                        ];
+                        fills_to_draw.push(
+                            Polygon::new(PlotPoints::from(rect))
+                                .fill_color(color.gamma_multiply(0.3))
+                                .stroke(egui::Stroke::NONE)
+                                .name(&label),
+                        );
+                    }
+                }
+                let points = Self::digital_wave_points(
+                    intervals,
+                    warp.to_display(global_min_time),
+                    warp.to_display(global_max_time),
+                    y_offset,
+                );
+                if export_svg {
+                    svg_figure
+                        .lines
+                        .push((color, sig.line_width, points.clone()));
+                }
+                let line = Line::new(PlotPoints::from(points))
+                    .color(color)
+                    .width(sig.line_width)
+                    .name(label);
                 lines_to_draw.push(line);
             }
 
-            egui_plot::Plot::new("global_digital_wave_plot")
+            let time_axis_decimals = self.user_settings.time_axis_decimals;
+            let display_timezone = self.user_settings.display_timezone;
+            let relative_time = self.relative_time;
+            let highlight_time = self.highlight_time.map(|t| warp.to_display(t));
+            let cursor_time = self.cursor_time.map(|t| warp.to_display(t));
+            let goto_time = self.goto_time.take().map(|t| warp.to_display(t));
+            let lane_separators = self.user_settings.lane_separators;
+            let hover_crosshair = self.user_settings.hover_crosshair;
+            let gap_regions = &self.gap_finder.gaps;
+            // 軸ラベルとカーソル位置の表示で共通の時刻フォーマット
+            let format_time = move |t: f64| {
+                if relative_time {
+                    format_relative_time(t - global_min_time, time_axis_decimals)
+                } else {
+                    format_time_of_day(t, time_axis_decimals, display_timezone)
+                }
+            };
+            // 今の表示範囲 (なければデータ全体) を SVG にする
+            let [bg_r, bg_g, bg_b] = self.user_settings.plot_background_color;
+            let [grid_r, grid_g, grid_b] = self.user_settings.plot_grid_color;
+            let svg = export_svg.then(|| {
+                let x_range = match self.plot_view {
+                    Some((x_min, x_max, _)) => (x_min, x_max),
+                    None => (
+                        warp.to_display(global_min_time),
+                        warp.to_display(global_max_time),
+                    ),
+                };
+                svg_figure.render(
+                    x_range,
+                    (total * 2 + 1) as f64,
+                    Color32::from_rgb(bg_r, bg_g, bg_b),
+                    Color32::from_rgb(grid_r, grid_g, grid_b),
+                    |x| format_time(warp.to_real(x)),
+                )
+            });
+            // 軸ラベルのフォーマッタは 'static なので写像の複製を渡す
+            let axis_warp = TimeWarp {
+                gaps: warp.gaps.clone(),
+                width: warp.width,
+            };
+            // タブごとに別のプロットとして表示範囲を保持する
+            // 右ドラッグで矩形ズーム、Reset Zoom でデータ全体の表示に戻す
+            let mut plot = egui_plot::Plot::new(("global_digital_wave_plot", active_tab))
+                .allow_boxed_zoom(true)
+                .boxed_zoom_pointer_button(egui::PointerButton::Secondary)
                 .min_size(ui.available_size())
-                .include_x(global_min_time)
-                .include_x(global_max_time)
+                .include_x(warp.to_display(global_min_time))
+                .include_x(warp.to_display(global_max_time))
                 .x_axis_formatter(
-                    |grid_mark: egui_plot::GridMark, _range: &RangeInclusive<f64>| {
-                        let x = grid_mark.value;
-                        let base_dt = Utc.timestamp_opt(0, 0).unwrap();
-                        let dt = base_dt + Duration::milliseconds((x * 1000.0) as i64);
-                        dt.naive_utc().format("%H:%M:%S%.3f").to_string()
+                    move |grid_mark: egui_plot::GridMark, _range: &RangeInclusive<f64>| {
+                        format_time(axis_warp.to_real(grid_mark.value))
                     },
                 )
                 .y_axis_formatter(
@@ -856,23 +6429,814 @@ impl eframe::App for MyApp {
                         offset_map.get(&y_int).cloned().unwrap_or_default()
                     },
                 )
-                .legend(Legend::default())
-                .show(ui, |plot_ui: &mut PlotUi| {
-                    for line in lines_to_draw {
-                        plot_ui.line(line);
+                .legend(Legend::default());
+            if std::mem::take(&mut self.reset_plot_view) {
+                plot = plot.reset();
+            }
+            // プロット領域だけ背景色とグリッド線の色を差し替える
+            // (egui_plot はグリッド線の色をテキスト色から作るため、軸の目盛りと凡例の文字色も変わる)
+            let plot_response = ui
+                .scope(|ui| {
+                    ui.visuals_mut().extreme_bg_color = Color32::from_rgb(bg_r, bg_g, bg_b);
+                    ui.visuals_mut().override_text_color =
+                        Some(Color32::from_rgb(grid_r, grid_g, grid_b));
+                    plot.show(ui, |plot_ui: &mut PlotUi| {
+                        // レーンの間 (偶数のオフセット) に薄い区切り線を引く
+                        if lane_separators {
+                            for k in 1..total {
+                                plot_ui.hline(
+                                    egui_plot::HLine::new((k * 2) as f64)
+                                        .color(Color32::from_gray(90).gamma_multiply(0.5))
+                                        .width(1.0),
+                                );
+                            }
+                        }
+                        // 「Find Gaps」で見つけた区間を網掛けする
+                        for &(start, end) in gap_regions {
+                            let (x0, x1) = (warp.to_display(start), warp.to_display(end));
+                            let top = (total * 2 + 1) as f64;
+                            plot_ui.polygon(
+                                Polygon::new(PlotPoints::from(vec![
+                                    [x0, 0.0],
+                                    [x1, 0.0],
+                                    [x1, top],
+                                    [x0, top],
+                                ]))
+                                .fill_color(Color32::from_rgb(255, 200, 0).gamma_multiply(0.12))
+                                .stroke(egui::Stroke::NONE)
+                                .name("Gaps"),
+                            );
+                        }
+                        // 縮めた無通信区間の位置に区切りの目印を描く
+                        for &(start, end, disp) in &warp.gaps {
+                            plot_ui.vline(
+                                VLine::new(disp + warp.width / 2.0)
+                                    .color(Color32::from_gray(120))
+                                    .style(egui_plot::LineStyle::dashed_loose())
+                                    .width(1.0)
+                                    .name(format!("Idle {:.3} s", end - start)),
+                            );
+                        }
+                        for fill in fills_to_draw {
+                            plot_ui.polygon(fill);
+                        }
+                        for line in lines_to_draw {
+                            plot_ui.line(line);
+                        }
+                        if !search_markers.is_empty() {
+                            plot_ui.points(
+                                Points::new(PlotPoints::from(search_markers))
+                                    .shape(MarkerShape::Diamond)
+                                    .radius(5.0)
+                                    .color(Color32::from_rgb(255, 140, 0))
+                                    .name("Value search"),
+                            );
+                        }
+                        // 「Go to」の時刻を今のズーム幅のまま中央に表示する
+                        if let Some(t) = goto_time {
+                            let bounds = plot_ui.plot_bounds();
+                            let half = bounds.width() / 2.0;
+                            plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
+                                [t - half, bounds.min()[1]],
+                                [t + half, bounds.max()[1]],
+                            ));
+                        }
+                        if let Some(t) = highlight_time {
+                            plot_ui.vline(
+                                VLine::new(t)
+                                    .color(Color32::from_rgb(255, 0, 255))
+                                    .width(1.5),
+                            );
+                        }
+                        if let Some(t) = cursor_time {
+                            plot_ui.vline(
+                                VLine::new(t)
+                                    .color(Color32::from_rgb(0, 200, 255))
+                                    .width(1.5)
+                                    .name("Cursor"),
+                            );
+                        }
+                        for (label, color, sig, y_offset) in analog_tracks {
+                            Self::build_analog_track(
+                                plot_ui,
+                                sig,
+                                y_offset,
+                                color,
+                                &label,
+                                user_settings,
+                                &warp,
+                            );
+                        }
+                        for (label, color, samples, y_offset) in state_tracks {
+                            Self::build_state_track(
+                                plot_ui,
+                                samples,
+                                global_max_time,
+                                y_offset,
+                                color,
+                                &label,
+                                &warp,
+                            );
+                        }
+                        // マウス位置に縦線と時刻を表示する
+                        let pointer = plot_ui.pointer_coordinate();
+                        if let Some(pos) = pointer.filter(|_| hover_crosshair) {
+                            let bounds = plot_ui.plot_bounds();
+                            let crosshair_color = Color32::from_gray(200).gamma_multiply(0.6);
+                            plot_ui.vline(VLine::new(pos.x).color(crosshair_color).width(1.0));
+                            plot_ui.text(
+                                Text::new(
+                                    PlotPoint::new(pos.x, bounds.max()[1]),
+                                    egui::RichText::new(format!(
+                                        " {}",
+                                        format_time(warp.to_real(pos.x))
+                                    ))
+                                    .small()
+                                    .color(Color32::WHITE),
+                                )
+                                .anchor(egui::Align2::LEFT_TOP),
+                            );
+                        }
+                        pointer
+                    })
+                })
+                .inner;
+
+            // レーンをクリックしたら左ペインの該当シグナルへスクロールして強調表示する
+            if plot_response.response.clicked() {
+                if let Some(pos) = plot_response.inner {
+                    // クリックした時刻をインスペクタのカーソルにする
+                    self.cursor_time = Some(warp.to_real(pos.x));
+                    if let Some((_, file_index, signal)) = lanes
+                        .iter()
+                        .find(|(y_offset, _, _)| (pos.y - (y_offset + 0.5)).abs() <= 1.0)
+                    {
+                        self.tree_focus = Some(TreeFocus {
+                            file_index: *file_index,
+                            signal: signal.clone(),
+                            until: ui.input(|i| i.time) + 1.5,
+                            scroll_pending: true,
+                        });
                     }
-                });
+                }
+            }
+
+            if let Some(svg) = svg {
+                self.save_svg(svg, "timing_plot.svg");
+            }
+
+            self.plot_rect = Some(plot_response.response.rect);
+
+            // 表示範囲が変わったら LOD を計算し直すため再描画する
+            let bounds = plot_response.transform.bounds();
+            let view = (
+                bounds.min()[0],
+                bounds.max()[0],
+                plot_response.response.rect.width(),
+            );
+            self.plot_time_range =
+                Some((warp.to_real(bounds.min()[0]), warp.to_real(bounds.max()[0])));
+            if self.plot_view != Some(view) {
+                self.plot_view = Some(view);
+                if self.user_settings.lod_enabled {
+                    ui.ctx().request_repaint();
+                }
+            }
         });
+
+        // このフレームで表示を切り替えた分も含めて上限を確認する
+        if self.enforce_visible_limit() {
+            ctx.request_repaint();
+        }
     }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let app = MyApp::new();
+    let mut app = MyApp::new();
+    // 引数で渡されたファイルを起動後に開く
+    app.startup_paths = std::env::args().skip(1).collect();
     let native_options = eframe::NativeOptions::default();
-    eframe::run_native(
-        "Log Analyzer",
-        native_options,
-        Box::new(|_cc| Ok(Box::new(app))),
-    )?;
+    eframe::run_native(APP_TITLE, native_options, Box::new(|_cc| Ok(Box::new(app))))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// JSON 文字列のデータファイルを "test.json" として読み込む
+    fn load(json: &str, settings: &UserSettings) -> FileData {
+        let data_file = parse_data_file(json).expect("valid data file");
+        FileData::from_data_file(data_file, "test.json", settings)
+    }
+
+    #[test]
+    fn empty_logs_are_reported() {
+        let file_data = load(r#"{"logs": []}"#, &UserSettings::default());
+        assert!(file_data.logs.is_empty());
+        assert!(file_data
+            .load_messages
+            .iter()
+            .any(|m| m == "test contains 0 log entries."));
+    }
+
+    #[test]
+    fn parses_bare_log_array() {
+        let data_file = parse_data_file(
+            r#"[
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ONOFF", "name": "A", "value": "ON"},
+                {"timestamp": "2025-03-01T00:00:01.000Z", "type": "ONOFF", "name": "A", "value": "OFF"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(data_file.logs.len(), 2);
+        assert_eq!(data_file.logs[1].value, "OFF");
+        assert!(data_file.default_visibility.is_none());
+    }
+
+    #[test]
+    fn duration_logs_become_intervals() {
+        let file_data = load(
+            r#"{"logs": [
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "DURATION", "name": "MS", "value": 1500},
+                {"timestamp": "2025-03-01T00:00:10.000Z", "type": "DURATION", "name": "END",
+                 "value": {"end": "2025-03-01T00:00:12.500Z"}}
+            ]}"#,
+            &UserSettings::default(),
+        );
+        let ms = &file_data.signals["MS"].on_intervals;
+        assert_eq!(ms.len(), 1);
+        assert!((ms[0].end - ms[0].start - 1.5).abs() < 1e-6);
+        let end = &file_data.signals["END"].on_intervals;
+        assert_eq!(end.len(), 1);
+        assert!((end[0].end - end[0].start - 2.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn strips_line_comments() {
+        let json = r#"{
+            // 先頭のコメント
+            "logs": [
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ONOFF", "name": "A", "value": "ON"} // 行末
+            ]
+        }"#;
+        let data_file = parse_data_file(&strip_json_comments(json)).unwrap();
+        assert_eq!(data_file.logs.len(), 1);
+    }
+
+    #[test]
+    fn strips_block_comments() {
+        let json = r#"{
+            "logs": [ /* 複数行の
+                         コメント */
+                {"timestamp": "2025-03-01T00:00:00.000Z", /* 途中 */ "type": "ONOFF", "name": "A", "value": "ON"}
+            ]
+        }"#;
+        let data_file = parse_data_file(&strip_json_comments(json)).unwrap();
+        assert_eq!(data_file.logs.len(), 1);
+        assert_eq!(data_file.logs[0].kind, "ONOFF");
+    }
+
+    #[test]
+    fn keeps_comment_markers_inside_strings() {
+        let json = r#"{"logs": [
+            {"timestamp": "2025-03-01T00:00:00.000Z", "type": "STATE", "name": "URL",
+             "value": "http://x", "comment": "/* not a comment */"}
+        ]}"#;
+        let data_file = parse_data_file(&strip_json_comments(json)).unwrap();
+        assert_eq!(data_file.logs[0].value, "http://x");
+        assert_eq!(
+            data_file.logs[0].comment.as_deref(),
+            Some("/* not a comment */")
+        );
+    }
+
+    #[test]
+    fn debounce_drops_only_short_pulses() {
+        let settings = UserSettings {
+            debounce_ms: 50.0,
+            ..UserSettings::default()
+        };
+        let file_data = load(
+            r#"{"logs": [
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ONOFF", "name": "SW", "value": "ON"},
+                {"timestamp": "2025-03-01T00:00:00.010Z", "type": "ONOFF", "name": "SW", "value": "OFF"},
+                {"timestamp": "2025-03-01T00:00:01.000Z", "type": "ONOFF", "name": "SW", "value": "ON"},
+                {"timestamp": "2025-03-01T00:00:01.200Z", "type": "ONOFF", "name": "SW", "value": "OFF"}
+            ]}"#,
+            &settings,
+        );
+        let intervals = &file_data.signals["SW"].on_intervals;
+        assert_eq!(intervals.len(), 1);
+        assert!((intervals[0].end - intervals[0].start - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn signal_in_two_groups_goes_to_the_first() {
+        let file_data = load(
+            r#"{"logs": [
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ARROW", "group": "g1", "name": "A", "value": ""},
+                {"timestamp": "2025-03-01T00:00:01.000Z", "type": "ARROW", "group": "g2", "name": "A", "value": ""}
+            ]}"#,
+            &UserSettings::default(),
+        );
+        let expected =
+            "test: signals appear in multiple groups and are shown only in the first: A (g1, g2)";
+        assert!(file_data.load_messages.iter().any(|m| m == expected));
+        let a = "A".to_string();
+        assert!(file_data.groups["g1"].signals.contains(&a));
+        assert!(!file_data
+            .groups
+            .get("g2")
+            .is_some_and(|g| g.signals.contains(&a)));
+    }
+
+    #[test]
+    fn comma_decimal_separator_is_accepted() {
+        let dot = parse_timestamp_to_f64("2025-03-01 12:00:00.123");
+        assert_ne!(dot, 0.0);
+        assert_eq!(parse_timestamp_to_f64("2025-03-01 12:00:00,123"), dot);
+    }
+
+    #[test]
+    fn other_commas_are_left_alone() {
+        assert_eq!(
+            normalize_decimal_comma("2025-03-01 12:00:00,123"),
+            "2025-03-01 12:00:00.123"
+        );
+        assert_eq!(
+            normalize_decimal_comma("a,b 12:00:00, 1,2"),
+            "a,b 12:00:00, 1,2"
+        );
+    }
+
+    /// 設定ファイルに左右されないよう、既定の設定でアプリを作る
+    fn test_app() -> MyApp {
+        let mut app = MyApp::new();
+        app.user_settings = UserSettings::default();
+        app
+    }
+
+    const TWO_SIGNALS: &str = r#"{"logs": [
+        {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ONOFF", "name": "X", "value": "ON"},
+        {"timestamp": "2025-03-01T00:00:01.000Z", "type": "ONOFF", "name": "X", "value": "OFF"},
+        {"timestamp": "2025-03-01T00:00:02.000Z", "type": "ONOFF", "name": "Y", "value": "ON"},
+        {"timestamp": "2025-03-01T00:00:03.000Z", "type": "ONOFF", "name": "Y", "value": "OFF"}
+    ]}"#;
+
+    #[test]
+    fn merge_prefixes_colliding_signals_and_applies_offsets() {
+        let mut app = test_app();
+        let settings = UserSettings::default();
+        for name in ["a", "b"] {
+            let mut file_data = load(TWO_SIGNALS, &settings);
+            file_data.file_name = name.to_string();
+            app.add_file(file_data);
+        }
+        app.open_files[0].signals.get_mut("Y").unwrap().visible = true;
+        app.open_files[1].signals.get_mut("Y").unwrap().visible = false;
+
+        app.merge_open_files(&[(0, 0.0), (1, 5.0)]);
+
+        assert_eq!(app.open_files.len(), 1);
+        let merged = &app.open_files[0];
+        assert_eq!(merged.file_name, "a + b");
+        let mut names: Vec<&str> = merged.signals.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, ["a/X", "a/Y", "b/X", "b/Y"]);
+        assert!(merged.signals["a/Y"].visible);
+        assert!(!merged.signals["b/Y"].visible);
+        // b の X は 5 秒ずらして入っている
+        let a_x = &merged.signals["a/X"].on_intervals;
+        let b_x = &merged.signals["b/X"].on_intervals;
+        assert!((b_x[0].start - a_x[0].start - 5.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn merge_keeps_unique_signal_names() {
+        let mut app = test_app();
+        let settings = UserSettings::default();
+        app.add_file(load(TWO_SIGNALS, &settings));
+        app.add_file(load(
+            r#"{"logs": [
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ONOFF", "name": "Z", "value": "ON"}
+            ]}"#,
+            &settings,
+        ));
+
+        app.merge_open_files(&[(0, 0.0), (1, 0.0)]);
+
+        let mut names: Vec<&str> = app.open_files[0]
+            .signals
+            .keys()
+            .map(String::as_str)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["X", "Y", "Z"]);
+        // 同名ファイルには連番が付く
+        assert_eq!(app.open_files[0].file_name, "test + test (2)");
+    }
+
+    #[test]
+    fn forget_removed_files_renumbers_file_state() {
+        let mut app = test_app();
+        let settings = UserSettings::default();
+        for _ in 0..3 {
+            app.add_file(load(TWO_SIGNALS, &settings));
+        }
+        app.pinned = [(0, "X"), (1, "X"), (2, "Y")]
+            .into_iter()
+            .map(|(i, name)| (i, name.to_string()))
+            .collect();
+        app.solo_group = Some((2, "G".to_string()));
+        app.open_files[0].derived.push(DerivedSignal {
+            source: "X".to_string(),
+            transform: DerivedTransform::Diff(2),
+            visible: true,
+        });
+        app.open_files[2].derived.push(DerivedSignal {
+            source: "X".to_string(),
+            transform: DerivedTransform::Diff(1),
+            visible: true,
+        });
+
+        app.open_files.remove(1);
+        app.forget_removed_files(&[1]);
+
+        let pinned: Vec<(usize, &str)> = app
+            .pinned
+            .iter()
+            .map(|(i, name)| (*i, name.as_str()))
+            .collect();
+        assert_eq!(pinned, [(0, "X"), (1, "Y")]);
+        assert_eq!(app.solo_group, Some((1, "G".to_string())));
+        // 取り除いたファイルとの差分は消え、残ったファイルとの差分は番号を詰める
+        assert!(matches!(
+            app.open_files[0].derived[0].transform,
+            DerivedTransform::Diff(1)
+        ));
+        assert!(app.open_files[1].derived.is_empty());
+    }
+
+    #[test]
+    fn unclosed_on_is_not_idle() {
+        let json = r#"{"logs": [
+            {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ONOFF", "name": "A", "value": "ON"},
+            {"timestamp": "2025-03-01T00:00:05.000Z", "type": "ONOFF", "name": "B", "value": "ON"},
+            {"timestamp": "2025-03-01T00:00:06.000Z", "type": "ONOFF", "name": "B", "value": "OFF"}
+        ]}"#;
+        let mut settings = UserSettings {
+            hide_idle_on_load: true,
+            default_show_all: true,
+            ..UserSettings::default()
+        };
+        let file_data = load(json, &settings);
+        // 既定では末尾まで伸ばさないが、エッジはあったので idle ではない
+        let a = &file_data.signals["A"];
+        assert!(a.on_intervals.is_empty());
+        assert!(!a.idle);
+        assert!(a.visible);
+
+        settings.extend_unclosed_on = true;
+        let file_data = load(json, &settings);
+        let a = &file_data.signals["A"];
+        assert_eq!(a.on_intervals.len(), 1);
+        assert!((a.on_intervals[0].end - file_data.max_time).abs() < 1e-9);
+        assert!(!a.idle);
+    }
+
+    #[test]
+    fn rfc3339_offsets_are_converted_to_utc() {
+        let utc = parse_timestamp_to_f64("2025-03-01T00:00:00.250Z");
+        assert_eq!(parse_timestamp_to_f64("2025-03-01T09:00:00.250+09:00"), utc);
+        assert_eq!(parse_timestamp_to_f64("2025-02-28T19:00:00.250-05:00"), utc);
+        // オフセットなしは UTC とみなす
+        assert_eq!(parse_timestamp_to_f64("2025-03-01T00:00:00.250"), utc);
+        assert_eq!(
+            parse_timestamp_to_epoch("2025-03-01T09:00:00.250+09:00"),
+            Some((1_740_787_200, 250_000_000))
+        );
+    }
+
+    #[test]
+    fn groups_by_extra_field() {
+        let settings = UserSettings {
+            group_by_field: "device".to_string(),
+            ..UserSettings::default()
+        };
+        let file_data = load(
+            r#"{"logs": [
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ARROW", "group": "g", "device": "dev1", "name": "A", "value": ""},
+                {"timestamp": "2025-03-01T00:00:01.000Z", "type": "ARROW", "group": "g", "device": "dev2", "name": "B", "value": ""},
+                {"timestamp": "2025-03-01T00:00:02.000Z", "type": "ARROW", "group": "g", "name": "C", "value": ""}
+            ]}"#,
+            &settings,
+        );
+        assert_eq!(file_data.groups["dev1"].signals, ["A"]);
+        assert_eq!(file_data.groups["dev2"].signals, ["B"]);
+        // フィールドが無いログはグループに入らない
+        assert!(!file_data.groups.contains_key("g"));
+        assert!(file_data
+            .groups
+            .values()
+            .all(|g| !g.signals.contains(&"C".to_string())));
+    }
+
+    #[test]
+    fn groups_by_builtin_field() {
+        let settings = UserSettings {
+            group_by_field: "type".to_string(),
+            ..UserSettings::default()
+        };
+        let file_data = load(
+            r#"{"logs": [
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ARROW", "name": "A", "value": ""},
+                {"timestamp": "2025-03-01T00:00:01.000Z", "type": "STATE", "name": "B", "value": "idle"}
+            ]}"#,
+            &settings,
+        );
+        assert_eq!(file_data.groups["ARROW"].signals, ["A"]);
+        assert_eq!(file_data.groups["STATE"].signals, ["B"]);
+    }
+
+    #[test]
+    fn multiple_groups_warning_uses_group_by_field() {
+        let settings = UserSettings {
+            group_by_field: "device".to_string(),
+            ..UserSettings::default()
+        };
+        // group フィールドは同じでも、device が違えば警告する
+        let file_data = load(
+            r#"{"logs": [
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ARROW", "group": "g", "device": "dev1", "name": "A", "value": ""},
+                {"timestamp": "2025-03-01T00:00:01.000Z", "type": "ARROW", "group": "g", "device": "dev2", "name": "A", "value": ""}
+            ]}"#,
+            &settings,
+        );
+        let expected =
+            "test: signals appear in multiple groups and are shown only in the first: A (dev1, dev2)";
+        assert!(file_data.load_messages.iter().any(|m| m == expected));
+    }
+
+    #[test]
+    fn strict_kinds_reports_and_skips_unknown_types() {
+        let json = r#"{"logs": [
+            {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ONOFF", "name": "A", "value": "ON"},
+            {"timestamp": "2025-03-01T00:00:01.000Z", "type": "ONOFF", "name": "A", "value": "OFF"},
+            {"timestamp": "2025-03-01T00:00:02.000Z", "type": "onoff", "name": "A", "value": "ON"},
+            {"timestamp": "2025-03-01T00:00:03.000Z", "type": "onoff", "name": "A", "value": "OFF"},
+            {"timestamp": "2025-03-01T00:00:04.000Z", "type": "LEVEL", "name": "B", "value": 1}
+        ]}"#;
+        let strict = UserSettings {
+            strict_kinds: true,
+            ..UserSettings::default()
+        };
+        let file_data = load(json, &strict);
+        let expected = "test: unrecognized log types were ignored: \"LEVEL\" x1, \"onoff\" x2";
+        assert!(file_data.load_messages.iter().any(|m| m == expected));
+        assert_eq!(file_data.signals["A"].on_intervals.len(), 1);
+        assert!(file_data.signals["B"].on_intervals.is_empty());
+
+        // 既定では知らない type も短い区間として描く
+        let file_data = load(json, &UserSettings::default());
+        assert!(!file_data
+            .load_messages
+            .iter()
+            .any(|m| m.contains("unrecognized")));
+        assert_eq!(file_data.signals["B"].on_intervals.len(), 1);
+    }
+
+    #[test]
+    fn out_of_order_entries_are_counted_and_sorted() {
+        let file_data = load(
+            r#"{"logs": [
+                {"timestamp": "2025-03-01T00:00:02.000Z", "type": "ARROW", "name": "A", "value": ""},
+                {"timestamp": "2025-03-01T00:00:01.000Z", "type": "ARROW", "name": "A", "value": ""},
+                {"timestamp": "2025-03-01T00:00:03.000Z", "type": "ARROW", "name": "A", "value": ""},
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ARROW", "name": "A", "value": ""}
+            ]}"#,
+            &UserSettings::default(),
+        );
+        let expected = "test: 2 log entries were out of chronological order and have been sorted.";
+        assert!(file_data.load_messages.iter().any(|m| m == expected));
+        assert!(file_data
+            .logs
+            .windows(2)
+            .all(|w| w[0].timestamp_num <= w[1].timestamp_num));
+    }
+
+    #[test]
+    fn sorted_logs_report_nothing() {
+        let file_data = load(
+            r#"{"logs": [
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ARROW", "name": "A", "value": ""},
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ARROW", "name": "B", "value": ""}
+            ]}"#,
+            &UserSettings::default(),
+        );
+        assert!(file_data.load_messages.is_empty());
+    }
+
+    #[test]
+    fn strips_trailing_commas() {
+        assert_eq!(
+            strip_trailing_commas("{\"a\": [1, 2,], \"b\": {\"c\": 3,\n},}"),
+            "{\"a\": [1, 2], \"b\": {\"c\": 3\n}}"
+        );
+        // 文字列内のカンマと括弧はそのまま
+        assert_eq!(
+            strip_trailing_commas(r#"["a,]", "b\",}"]"#),
+            r#"["a,]", "b\",}"]"#
+        );
+    }
+
+    #[test]
+    fn settings_allow_comments_and_trailing_commas() {
+        let settings = MyApp::parse_settings(
+            r#"{
+                // 手で編集した設定
+                "debounce_ms": 5.0,
+                "strict_kinds": true,
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(settings.debounce_ms, 5.0);
+        assert!(settings.strict_kinds);
+    }
+
+    #[test]
+    fn settings_errors_report_the_original_parse_error() {
+        let err = MyApp::parse_settings(r#"{"debounce_ms": "fast"}"#).unwrap_err();
+        assert!(err.contains("invalid type"), "{}", err);
+    }
+
+    /// 一時ディレクトリにファイルを書き、そのパスを返す
+    fn write_temp_file(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn reads_manifest_files() {
+        let path = write_temp_file(
+            "manifest.json",
+            r#"{"files": [
+                {"path": "a.json", "offset": 0.5, "visible": ["SIG_A"]},
+                {"path": "/data/b.json"}
+            ]}"#,
+        );
+        let manifest = read_manifest(&path).expect("manifest");
+        fs::remove_file(&path).unwrap();
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(manifest.files[0].path, "a.json");
+        assert_eq!(manifest.files[0].offset, 0.5);
+        assert_eq!(manifest.files[0].visible, Some(vec!["SIG_A".to_string()]));
+        assert_eq!(manifest.files[1].offset, 0.0);
+        assert!(manifest.files[1].visible.is_none());
+    }
+
+    #[test]
+    fn data_files_are_not_manifests() {
+        let path = write_temp_file(
+            "data.json",
+            r#"{"logs": [
+                {"timestamp": "2025-03-01T00:00:00.000Z", "type": "ARROW", "name": "A", "value": ""}
+            ]}"#,
+        );
+        let manifest = read_manifest(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(manifest.is_none());
+        assert!(read_manifest("does-not-exist.json").is_none());
+    }
+
+    /// (start, end) の並びから区間集合を作る
+    fn intervals(pairs: &[(f64, f64)]) -> Vec<Interval> {
+        pairs
+            .iter()
+            .map(|&(start, end)| Interval { start, end })
+            .collect()
+    }
+
+    fn pairs(intervals: &[Interval]) -> Vec<(f64, f64)> {
+        intervals.iter().map(|iv| (iv.start, iv.end)).collect()
+    }
+
+    #[test]
+    fn interval_union() {
+        let a = intervals(&[(0.0, 2.0), (5.0, 6.0)]);
+        let b = intervals(&[(1.0, 3.0), (6.0, 7.0), (9.0, 10.0)]);
+        assert_eq!(
+            pairs(&union_intervals(&a, &b)),
+            [(0.0, 3.0), (5.0, 7.0), (9.0, 10.0)]
+        );
+        assert_eq!(pairs(&union_intervals(&a, &[])), pairs(&a));
+    }
+
+    #[test]
+    fn interval_intersection() {
+        let a = intervals(&[(0.0, 2.0), (5.0, 8.0)]);
+        let b = intervals(&[(1.0, 6.0), (7.0, 9.0)]);
+        assert_eq!(
+            pairs(&intersect_intervals(&a, &b)),
+            [(1.0, 2.0), (5.0, 6.0), (7.0, 8.0)]
+        );
+        // 端が接するだけの区間は共通部分にならない
+        let c = intervals(&[(2.0, 5.0)]);
+        assert!(intersect_intervals(&a, &c).is_empty());
+    }
+
+    #[test]
+    fn interval_subtraction() {
+        let a = intervals(&[(0.0, 4.0), (6.0, 8.0)]);
+        let b = intervals(&[(1.0, 2.0), (3.0, 7.0)]);
+        assert_eq!(
+            pairs(&subtract_intervals(&a, &b)),
+            [(0.0, 1.0), (2.0, 3.0), (7.0, 8.0)]
+        );
+        assert!(subtract_intervals(&[], &b).is_empty());
+        assert_eq!(pairs(&subtract_intervals(&a, &[])), pairs(&a));
+    }
+
+    #[test]
+    fn interval_xor() {
+        let a = intervals(&[(0.0, 4.0)]);
+        let b = intervals(&[(2.0, 6.0)]);
+        assert_eq!(pairs(&xor_intervals(&a, &b)), [(0.0, 2.0), (4.0, 6.0)]);
+        assert!(xor_intervals(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn bins_interval_durations() {
+        let (min_d, bin_width, counts) = bin_durations(&[0.001, 0.002, 0.002, 0.005], 4);
+        assert_eq!(min_d, 0.001);
+        assert!((bin_width - 0.001).abs() < 1e-12);
+        // 最大値は最後のビンに入る
+        assert_eq!(counts, [1, 2, 0, 1]);
+        // すべて同じ長さなら 1 ms 幅の最初のビンにまとめる
+        let (_, bin_width, counts) = bin_durations(&[0.5, 0.5], 3);
+        assert_eq!(bin_width, 0.001);
+        assert_eq!(counts, [2, 0, 0]);
+    }
+
+    #[test]
+    fn histogram_svg_draws_one_bar_per_bin() {
+        let svg = histogram_svg(
+            "a <b>",
+            1.0,
+            0.5,
+            &[3, 0, 1],
+            Color32::LIGHT_GREEN,
+            Color32::BLACK,
+            Color32::GRAY,
+        );
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains("a &lt;b&gt;"));
+        assert_eq!(svg.matches("<rect ").count(), 1 + 3);
+        // 横軸は最初のビンの下端から最後のビンの上端まで
+        assert!(svg.contains(">1.000 ms</text>"));
+        assert!(svg.contains(">2.500 ms</text>"));
+    }
+
+    #[test]
+    fn nanosecond_timestamps_round_trip() {
+        for ts in [
+            "2025-03-01T00:00:00.000000001Z",
+            "2025-03-01T12:34:56.123456789Z",
+            "2025-03-01T23:59:59.999999999Z",
+            "2025-03-02T00:00:00.500Z",
+        ] {
+            assert_eq!(format_iso(parse_timestamp_to_f64(ts)), ts);
+        }
+    }
+
+    #[test]
+    fn nanosecond_edges_are_not_quantized() {
+        let a = parse_timestamp_to_f64("2025-03-01T12:00:00.000000100Z");
+        let b = parse_timestamp_to_f64("2025-03-01T12:00:00.000000150Z");
+        assert!((b - a - 50e-9).abs() < 1e-12);
+        assert_eq!(
+            format_time_of_day(b, 9, DisplayTimezone::Utc),
+            "12:00:00.000000150"
+        );
+        assert_eq!(
+            format_time_of_day(b, 3, DisplayTimezone::Utc),
+            "12:00:00.000"
+        );
+        assert_eq!(format_relative_time(b - a, 9), "+00:00.000000050");
+    }
+
+    #[test]
+    fn go_to_input_keeps_nanoseconds() {
+        let reference = parse_timestamp_to_f64("2025-03-01T08:00:00Z");
+        let t = parse_time_input("12:00:00.000000150", reference, DisplayTimezone::Utc).unwrap();
+        assert_eq!(format_iso(t), "2025-03-01T12:00:00.000000150Z");
+        let t = parse_time_input("2025-03-01T12:00:00.000000150Z", 0.0, DisplayTimezone::Utc);
+        assert_eq!(
+            t.map(format_iso).as_deref(),
+            Some("2025-03-01T12:00:00.000000150Z")
+        );
+        assert!(parse_time_input("2025-13-01", reference, DisplayTimezone::Utc).is_none());
+    }
+}